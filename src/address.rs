@@ -39,8 +39,81 @@ impl From<u32> for VirtualAddress {
     }
 }
 
+/// `AccessKind` describes what a memory access intends to do with the data found at an address.
+/// Writes carry the value to be stored, while executes read the data as an instruction fetch.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum AccessKind {
+    Read,
+    Write(i8),
+    Execute,
+}
+
+/// `MemoryAccess` pairs a virtual address with the kind of access requested at that location. It
+/// is the unit of work read from an address trace and handed to the virtual memory system.
+#[derive(PartialEq, Debug)]
+pub struct MemoryAccess {
+    pub virtual_address: VirtualAddress,
+    pub kind: AccessKind,
+}
+
+impl From<u32> for MemoryAccess {
+    /// Provided an address in the form of a 32-bit unsigned integer, create a read access for
+    /// the corresponding virtual address.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - 32-bit unsigned integer representing a virtual address location
+    fn from(value: u32) -> Self {
+        Self {
+            virtual_address: VirtualAddress::from(value),
+            kind: AccessKind::Read,
+        }
+    }
+}
+
+impl MemoryAccess {
+    /// Parse a single line of an address trace. A line holding only an address is treated as a
+    /// read, while a leading `R`, `W`, or `X` marks the kind explicitly. Writes must be followed by
+    /// the value to store.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - a trace line such as `16916`, `X 16916`, or `W 16916 42`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line is malformed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use virtual_memory_sim::address::{AccessKind, MemoryAccess};
+    /// let access = MemoryAccess::parse("W 16916 42");
+    /// assert_eq!(access.virtual_address.number_page, 66);
+    /// assert_eq!(access.kind, AccessKind::Write(42));
+    /// ```
+    pub fn parse(line: &str) -> Self {
+        let tokens = line.split_whitespace().collect::<Vec<&str>>();
+        let parse_address = |token: &str| token.parse::<u32>().expect("expected an integer value");
+        match tokens.as_slice() {
+            [address] => Self::from(parse_address(address)),
+            ["R" | "r", address] => Self::from(parse_address(address)),
+            ["X" | "x", address] => Self {
+                virtual_address: VirtualAddress::from(parse_address(address)),
+                kind: AccessKind::Execute,
+            },
+            ["W" | "w", address, value] => Self {
+                virtual_address: VirtualAddress::from(parse_address(address)),
+                kind: AccessKind::Write(value.parse::<i8>().expect("expected a byte value")),
+            },
+            _ => panic!("malformed trace line: '{}'", line.trim()),
+        }
+    }
+}
+
 /// `AddressReader` is a utility type responsible for sequentially obtaining "raw" address numbers
-/// from a text file. Those obtained can be used to access data from a virtual memory system.
+/// from a text file. Those obtained can be used to access data from a virtual memory system. Each
+/// address may optionally be marked as a read or a write (see `MemoryAccess::parse`).
 pub struct AddressReader {
     reader: BufReader<File>,
     pub line_number: u64,
@@ -49,7 +122,7 @@ pub struct AddressReader {
 impl AddressReader {
     /// Instantiate a new `AddressReader` struct for working with the provided text file. Ensure
     /// the content of the file contains only address numbers (no header information) and each line
-    /// contains only one address.
+    /// contains only one access.
     ///
     /// # Panics
     ///
@@ -66,7 +139,7 @@ impl AddressReader {
 }
 
 impl Iterator for AddressReader {
-    type Item = VirtualAddress;
+    type Item = MemoryAccess;
 
     fn next(&mut self) -> Option<Self::Item> {
         let mut buffer = String::new();
@@ -74,12 +147,8 @@ impl Iterator for AddressReader {
             Err(err) => panic!("error: {:?}", err),
             Ok(0) => None,
             Ok(_) => {
-                let value = buffer
-                    .trim()
-                    .parse::<u32>()
-                    .expect("expected an integer value");
                 self.line_number += 1;
-                Some(VirtualAddress::from(value))
+                Some(MemoryAccess::parse(&buffer))
             }
         }
     }
@@ -114,8 +183,34 @@ mod tests {
         #[test]
         fn iterator() {
             let mut reader = standard_reader();
-            assert_eq!(reader.next(), Some(VirtualAddress::from(16916)));
-            assert_eq!(reader.last(), Some(VirtualAddress::from(12107)));
+            assert_eq!(reader.next(), Some(MemoryAccess::from(16916)));
+            assert_eq!(reader.last(), Some(MemoryAccess::from(12107)));
+        }
+    }
+
+    #[cfg(test)]
+    mod memory_access_tests {
+
+        use super::*;
+
+        #[test]
+        fn parse() {
+            assert_eq!(MemoryAccess::parse("16916\n"), MemoryAccess::from(16916));
+            assert_eq!(MemoryAccess::parse("R 16916"), MemoryAccess::from(16916));
+            assert_eq!(MemoryAccess::parse("X 16916").kind, AccessKind::Execute);
+            assert_eq!(
+                MemoryAccess::parse("w 16916 -3"),
+                MemoryAccess {
+                    virtual_address: VirtualAddress::from(16916),
+                    kind: AccessKind::Write(-3),
+                }
+            );
+        }
+
+        #[test]
+        #[should_panic]
+        fn parse_malformed() {
+            MemoryAccess::parse("W 16916");
         }
     }
 
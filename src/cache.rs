@@ -0,0 +1,256 @@
+use crate::config::Config;
+use crate::tracker::Tracker;
+use linked_hash_map::LinkedHashMap;
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+/// The `CachePolicy` enum is the replacement policy of a set-associative cache: the least
+/// recently used line of a full set is replaced (`Lru`), or the line which has been in the set
+/// longest regardless of use (`Fifo`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CachePolicy {
+    Lru,
+    Fifo,
+}
+
+impl FromStr for CachePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lru" => Ok(Self::Lru),
+            "fifo" => Ok(Self::Fifo),
+            _ => Err(format!("unknown cache policy: '{}'", s)),
+        }
+    }
+}
+
+/// The `CacheLevel` struct models a single level of a set-associative data cache. Only the tags
+/// of the lines held are tracked, since the simulated frames already hold the data itself. A line
+/// is placed in the set selected by its line number modulo the number of sets. A level with a
+/// size of zero holds nothing and records neither hits nor misses.
+///
+/// Alongside the sets, the level tracks the lines a fully associative LRU cache of the same
+/// capacity would hold. A miss on a line which that cache would still hold is a conflict miss,
+/// caused by too many lines mapping to the same set rather than by the capacity of the level.
+pub struct CacheLevel {
+    line_size: usize,
+    ways: usize,
+    sets: Vec<VecDeque<usize>>,
+    policy: CachePolicy,
+    associative: LinkedHashMap<usize, ()>,
+    hits: usize,
+    misses: usize,
+    conflict_misses: usize,
+}
+
+impl CacheLevel {
+    /// Create a new, empty `CacheLevel` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - the capacity of the level in bytes.
+    /// * `line_size` - the size of each line in bytes.
+    /// * `ways` - the number of lines in each set.
+    /// * `policy` - the replacement policy applied to a full set.
+    pub fn build(size: usize, line_size: usize, ways: usize, policy: CachePolicy) -> Self {
+        Self {
+            line_size,
+            ways,
+            sets: vec![VecDeque::with_capacity(ways); size / (line_size * ways)],
+            policy,
+            associative: LinkedHashMap::new(),
+            hits: 0,
+            misses: 0,
+            conflict_misses: 0,
+        }
+    }
+
+    /// Look up the line holding the provided physical address, returning whether it was found.
+    /// Should it be missing, the line is placed in its set, replacing another should the set be
+    /// full.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - the physical address accessed.
+    pub fn access(&mut self, address: usize) -> bool {
+        if self.sets.is_empty() {
+            return false;
+        }
+        let line = address / self.line_size;
+        let count = self.sets.len();
+        let associative_hit = self.associative.remove(&line).is_some();
+        self.associative.insert(line, ());
+        if self.associative.len() > count * self.ways {
+            self.associative.pop_front();
+        }
+        let set = &mut self.sets[line % count];
+        if let Some(position) = set.iter().position(|tag| *tag == line) {
+            if self.policy == CachePolicy::Lru {
+                set.remove(position);
+                set.push_back(line);
+            }
+            self.hits += 1;
+            return true;
+        }
+        if set.len() == self.ways {
+            set.pop_front();
+        }
+        set.push_back(line);
+        self.misses += 1;
+        if associative_hit {
+            self.conflict_misses += 1;
+        }
+        false
+    }
+}
+
+/// The `DataCache` struct is a two-level data cache hierarchy indexed and tagged with physical
+/// addresses. Every access looks up the first level, and only accesses which miss the first level
+/// look up the second. Lines missing from a level are placed in it, without the levels being kept
+/// inclusive or exclusive of each other.
+pub struct DataCache {
+    l1: CacheLevel,
+    l2: CacheLevel,
+}
+
+impl DataCache {
+    /// Create a new, empty `DataCache` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - An instance of the configuration struct which contains the size, line size,
+    ///   and associativity of each level along with the replacement policy.
+    ///
+    /// # Panics
+    ///
+    /// The call will panic if the replacement policy is unknown.
+    pub fn build(config: &Config) -> Self {
+        let policy: CachePolicy = config
+            .cache_policy
+            .parse()
+            .unwrap_or_else(|err| panic!("{}", err));
+        Self {
+            l1: CacheLevel::build(
+                config.size_l1 as usize,
+                config.size_l1_line as usize,
+                config.l1_ways as usize,
+                policy,
+            ),
+            l2: CacheLevel::build(
+                config.size_l2 as usize,
+                config.size_l2_line as usize,
+                config.l2_ways as usize,
+                policy,
+            ),
+        }
+    }
+
+    /// Access the provided physical address through the hierarchy.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - the physical address accessed.
+    pub fn access(&mut self, address: usize) {
+        if !self.l1.access(address) {
+            self.l2.access(address);
+        }
+    }
+
+    /// Copy the statistics of the hierarchy into the provided tracker.
+    ///
+    /// # Arguments
+    ///
+    /// * `tracker` - the tracker receiving the statistics.
+    pub fn record(&self, tracker: &mut Tracker) {
+        tracker.l1_hits = self.l1.hits;
+        tracker.l1_misses = self.l1.misses;
+        tracker.l2_hits = self.l2.hits;
+        tracker.l2_misses = self.l2.misses;
+        tracker.l1_conflict_misses = self.l1.conflict_misses;
+        tracker.l2_conflict_misses = self.l2.conflict_misses;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[cfg(test)]
+    mod cache_level_tests {
+
+        use super::*;
+
+        #[test]
+        fn lru() {
+            let mut level = CacheLevel::build(64, 16, 2, CachePolicy::Lru);
+            assert!(!level.access(0x00));
+            assert!(level.access(0x0F));
+            assert!(!level.access(0x10));
+            assert!(!level.access(0x40));
+            assert!(level.access(0x00));
+            assert!(!level.access(0x80));
+            assert!(level.access(0x00));
+            assert!(!level.access(0x40));
+            assert!(level.access(0x10));
+            assert_eq!((level.hits, level.misses), (4, 5));
+            assert_eq!(level.conflict_misses, 1);
+        }
+
+        #[test]
+        fn conflict_misses() {
+            let mut level = CacheLevel::build(64, 16, 2, CachePolicy::Lru);
+            for address in [0x00, 0x40, 0x80, 0x00, 0x10, 0x20] {
+                level.access(address);
+            }
+            assert_eq!((level.misses, level.conflict_misses), (6, 1));
+            assert!(!level.access(0x40));
+            assert_eq!(level.conflict_misses, 1);
+        }
+
+        #[test]
+        fn fifo() {
+            let mut level = CacheLevel::build(64, 16, 2, CachePolicy::Fifo);
+            for address in [0x00, 0x40, 0x00, 0x80] {
+                level.access(address);
+            }
+            assert!(!level.access(0x00));
+            assert!(level.access(0x80));
+        }
+
+        #[test]
+        fn disabled() {
+            let mut level = CacheLevel::build(0, 16, 2, CachePolicy::Lru);
+            assert!(!level.access(0x00));
+            assert!(!level.access(0x00));
+            assert_eq!((level.hits, level.misses), (0, 0));
+        }
+    }
+
+    #[cfg(test)]
+    mod data_cache_tests {
+
+        use super::*;
+        use clap::Parser;
+
+        #[test]
+        fn access() {
+            let mut config = Config::parse_from(["virtual_memory_sim"]);
+            config.size_l1 = 32;
+            config.size_l1_line = 16;
+            config.l1_ways = 1;
+            config.size_l2 = 128;
+            config.size_l2_line = 32;
+            config.l2_ways = 2;
+            let mut cache = DataCache::build(&config);
+            for address in [0x00, 0x20, 0x00, 0x04, 0x40, 0x00] {
+                cache.access(address);
+            }
+            let mut tracker = Tracker::new();
+            cache.record(&mut tracker);
+            assert_eq!((tracker.l1_hits, tracker.l1_misses), (1, 5));
+            assert_eq!((tracker.l2_hits, tracker.l2_misses), (2, 3));
+        }
+    }
+}
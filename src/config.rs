@@ -1,3 +1,8 @@
+use crate::cache::CachePolicy;
+use crate::device::DeviceKind;
+use crate::storage::OutOfRange;
+use crate::virtual_memory::FrameColoring;
+use crate::MASK_PAGE;
 use clap::Parser;
 use std::env;
 use std::process;
@@ -7,34 +12,207 @@ use std::process;
 pub struct Config {
     #[arg(long, default_value_t =  env_or_default_str("SIM_FILE_STORAGE", "BACKING_STORE.bin"))]
     pub file_storage: String,
+    #[arg(long, default_value_t =  env_or_default_str("SIM_STORAGE_KIND", "file"))]
+    pub storage_kind: String,
+
+    #[arg(long, default_value_t =  env_or_default_str("SIM_OUT_OF_RANGE", "error"))]
+    pub out_of_range: String,
+
     #[arg(long, default_value_t =  env_or_default_str("SIM_FILE_VALIDATION", "correct.txt"))]
     pub file_validation: String,
 
     #[arg(long, default_value_t =  env_or_default_str("SIM_FILE_ADDRESS", "addresses.txt"))]
     pub file_address: String,
 
+    #[arg(long, default_value_t =  env_or_default_str("SIM_FILE_SWAP", "SWAP.bin"))]
+    pub file_swap: String,
+
     #[arg(long, default_value_t = env_or_default_u32("SIM_SIZE_TABLE", 64))]
     pub size_table: u32,
 
+    #[arg(long, default_value_t = env_or_default_u32("SIM_SIZE_SWAP", 1024))]
+    pub size_swap: u32,
+
     #[arg(long, default_value_t =  env_or_default_u32("SIM_SIZE_TLB", 16))]
     pub size_tlb: u32,
 
     #[arg(long, default_value_t = env_or_default_u32("SIM_SIZE_FRAME", 256))]
     pub size_frame: u32,
 
-    #[arg(long, default_value_t = env_or_default_u32("SIM_DELAY_US", 250))]
-    pub delay_us: u32,
+    #[arg(long, default_value_t = env_or_default_u32("SIM_LATENCY_TLB", 1))]
+    pub latency_tlb: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_LATENCY_WALK_LEVEL", 100))]
+    pub latency_walk_level: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_LATENCY_MEMORY", 100))]
+    pub latency_memory: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_LATENCY_FAULT", 1_000))]
+    pub latency_fault: u32,
+
+    #[arg(long, default_value_t =  env_or_default_str("SIM_DEVICE", "ssd"))]
+    pub device: String,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_HDD_SEEK_NS", 4_000_000))]
+    pub hdd_seek_ns: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_HDD_RPM", 7_200))]
+    pub hdd_rpm: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_HDD_BANDWIDTH", 150))]
+    pub hdd_bandwidth: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_SSD_LATENCY_NS", 100_000))]
+    pub ssd_latency_ns: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_SSD_BANDWIDTH", 500))]
+    pub ssd_bandwidth: u32,
+
+    #[arg(long, default_value_t =  env_or_default_str("SIM_PREFETCHER", "none"))]
+    pub prefetcher: String,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_PREFETCH_WINDOW", 8))]
+    pub prefetch_window: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_SIZE_MARKOV_TABLE", 64))]
+    pub size_markov_table: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_FAULT_AROUND", 1))]
+    pub fault_around: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_WATERMARK_MIN", 0))]
+    pub watermark_min: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_WATERMARK_LOW", 0))]
+    pub watermark_low: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_WATERMARK_HIGH", 0))]
+    pub watermark_high: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_RECLAIM_INTERVAL", 0))]
+    pub reclaim_interval: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_SIZE_MODIFIED_LIST", 0))]
+    pub size_modified_list: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_SIZE_HUGE_PAGE", 1))]
+    pub size_huge_page: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_SIZE_THP", 1))]
+    pub size_thp: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_THP_SCAN_INTERVAL", 16))]
+    pub thp_scan_interval: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_MAX_PINNED_FRAMES", 16))]
+    pub max_pinned_frames: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_KSM_SCAN_INTERVAL", 0))]
+    pub ksm_scan_interval: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_SIZE_ZSWAP", 0))]
+    pub size_zswap: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_SIZE_L1", 1024))]
+    pub size_l1: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_SIZE_L1_LINE", 32))]
+    pub size_l1_line: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_L1_WAYS", 2))]
+    pub l1_ways: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_SIZE_L2", 8192))]
+    pub size_l2: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_SIZE_L2_LINE", 64))]
+    pub size_l2_line: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_L2_WAYS", 4))]
+    pub l2_ways: u32,
+
+    #[arg(long, default_value_t =  env_or_default_str("SIM_CACHE_POLICY", "lru"))]
+    pub cache_policy: String,
+
+    #[arg(long, default_value_t =  env_or_default_str("SIM_FRAME_COLORING", "none"))]
+    pub frame_coloring: String,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_PAGE_WALK_LEVELS", 2))]
+    pub page_walk_levels: u32,
+
+    #[arg(long, default_value_t = env_or_default_u32("SIM_SIZE_PWC", 0))]
+    pub size_pwc: u32,
 }
 
 impl Config {
-    /// Ensure the configuration is appropriately defined.
+    /// Ensure the configuration is appropriately defined, exiting with a message describing the
+    /// first problem found (see `check`) should it not be.
     pub fn validate(&self) {
-        if self.size_tlb == 0 || self.size_tlb > self.size_table {
-            eprintln!("'size_tlb' must be a non-zero value less than 'size_table'");
+        if let Err(message) = self.check() {
+            eprintln!("{}", message);
             process::exit(1);
+        }
+    }
+
+    /// Return a message describing the first problem found with the configuration, if any.
+    pub fn check(&self) -> Result<(), &'static str> {
+        if self.size_tlb == 0 || self.size_tlb > self.size_table {
+            Err("'size_tlb' must be a non-zero value less than 'size_table'")
         } else if f64::from(self.size_frame).log2().fract() != 0.0 {
-            eprintln!("'size_frame' must be a non-zero power of 2 integer value");
-            process::exit(1);
+            Err("'size_frame' must be a non-zero power of 2 integer value")
+        } else if !["file", "memory", "mmap"].contains(&self.storage_kind.as_str()) {
+            Err("'storage_kind' must be one of 'file', 'memory', or 'mmap'")
+        } else if self.out_of_range.parse::<OutOfRange>().is_err() {
+            Err("'out_of_range' must be one of 'error', 'zero-fill', or 'wrap'")
+        } else if !["none", "readahead", "stride", "markov"].contains(&self.prefetcher.as_str()) {
+            Err("'prefetcher' must be one of 'none', 'readahead', 'stride', or 'markov'")
+        } else if self.prefetcher != "none" && self.prefetch_window == 0 {
+            Err("'prefetch_window' must be a non-zero value when prefetching")
+        } else if self.prefetcher == "markov" && self.size_markov_table == 0 {
+            Err("'size_markov_table' must be a non-zero value for the markov prefetcher")
+        } else if !self.fault_around.is_power_of_two()
+            || self.fault_around > 1 << MASK_PAGE.count_ones()
+        {
+            Err("'fault_around' must be a power of 2 no greater than the number of pages")
+        } else if self.watermark_min > self.watermark_low
+            || self.watermark_low > self.watermark_high
+            || self.watermark_high >= self.size_table
+        {
+            Err("watermarks must satisfy 'min' <= 'low' <= 'high' < 'size_table'")
+        } else if !self.size_huge_page.is_power_of_two()
+            || self.size_huge_page > self.size_table - self.watermark_min
+        {
+            Err("'size_huge_page' must be a power of 2 no greater than the usable frames")
+        } else if !self.size_thp.is_power_of_two()
+            || self.size_thp > self.size_table - self.watermark_min
+        {
+            Err("'size_thp' must be a power of 2 no greater than the usable frames")
+        } else if self.size_thp > 1 && self.thp_scan_interval == 0 {
+            Err("'thp_scan_interval' must be non-zero when 'size_thp' exceeds 1")
+        } else if self.max_pinned_frames > self.size_table {
+            Err("'max_pinned_frames' must be no greater than 'size_table'")
+        } else if self.device.parse::<DeviceKind>().is_err() {
+            Err("'device' must be one of 'hdd' or 'ssd'")
+        } else if self.hdd_rpm == 0 || self.hdd_bandwidth == 0 || self.ssd_bandwidth == 0 {
+            Err("'hdd_rpm', 'hdd_bandwidth', and 'ssd_bandwidth' must be non-zero values")
+        } else if [
+            (self.size_l1, self.size_l1_line, self.l1_ways),
+            (self.size_l2, self.size_l2_line, self.l2_ways),
+        ]
+        .iter()
+        .any(|(size, line, ways)| {
+            !line.is_power_of_two() || *ways == 0 || !size.is_multiple_of(line * ways)
+        }) {
+            Err("cache sizes must be multiples of a power of 2 line size times the ways")
+        } else if self.cache_policy.parse::<CachePolicy>().is_err() {
+            Err("'cache_policy' must be one of 'lru' or 'fifo'")
+        } else if self.frame_coloring.parse::<FrameColoring>().is_err() {
+            Err("'frame_coloring' must be one of 'none', 'page-coloring', or 'bin-hopping'")
+        } else if self.page_walk_levels == 0 || self.page_walk_levels > MASK_PAGE.count_ones() {
+            Err("'page_walk_levels' must be a non-zero value no greater than the page bits")
+        } else {
+            Ok(())
         }
     }
 
@@ -69,12 +247,109 @@ fn env_or_default_str(varname: &str, default: &str) -> String {
 ///
 /// # Panics
 ///
-/// A panic will occur if the environment variable contains a non-integer value. 
+/// A panic will occur if the environment variable contains a non-integer value.
 fn env_or_default_u32(varname: &str, default: u32) -> u32 {
     match env::var(varname) {
         Ok(val) => val
             .parse()
-            .expect(&format!("expected unsigned int for env var: '{}'", varname)),
+            .unwrap_or_else(|_| panic!("expected unsigned int for env var: '{}'", varname)),
         _ => default,
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[cfg(test)]
+    mod config_tests {
+
+        use super::*;
+
+        #[test]
+        fn check() {
+            let config = Config::parse_from(["virtual_memory_sim"]);
+            assert_eq!(config.check(), Ok(()));
+
+            let config = Config::parse_from(["virtual_memory_sim", "--device", "tape"]);
+            assert_eq!(
+                config.check(),
+                Err("'device' must be one of 'hdd' or 'ssd'")
+            );
+
+            let config = Config::parse_from(["virtual_memory_sim", "--cache-policy", "bogus"]);
+            assert_eq!(
+                config.check(),
+                Err("'cache_policy' must be one of 'lru' or 'fifo'")
+            );
+
+            let rejected: [(&[&str], &str); 15] = [
+                (
+                    &["--storage-kind", "tape"],
+                    "'storage_kind' must be one of 'file', 'memory', or 'mmap'",
+                ),
+                (
+                    &["--out-of-range", "bogus"],
+                    "'out_of_range' must be one of 'error', 'zero-fill', or 'wrap'",
+                ),
+                (
+                    &["--prefetcher", "bogus"],
+                    "'prefetcher' must be one of 'none', 'readahead', 'stride', or 'markov'",
+                ),
+                (
+                    &["--prefetcher", "readahead", "--prefetch-window", "0"],
+                    "'prefetch_window' must be a non-zero value when prefetching",
+                ),
+                (
+                    &["--prefetcher", "markov", "--size-markov-table", "0"],
+                    "'size_markov_table' must be a non-zero value for the markov prefetcher",
+                ),
+                (
+                    &["--fault-around", "3"],
+                    "'fault_around' must be a power of 2 no greater than the number of pages",
+                ),
+                (
+                    &["--watermark-min", "4", "--watermark-low", "2"],
+                    "watermarks must satisfy 'min' <= 'low' <= 'high' < 'size_table'",
+                ),
+                (
+                    &["--size-huge-page", "3"],
+                    "'size_huge_page' must be a power of 2 no greater than the usable frames",
+                ),
+                (
+                    &["--size-thp", "128"],
+                    "'size_thp' must be a power of 2 no greater than the usable frames",
+                ),
+                (
+                    &["--size-thp", "2", "--thp-scan-interval", "0"],
+                    "'thp_scan_interval' must be non-zero when 'size_thp' exceeds 1",
+                ),
+                (
+                    &["--max-pinned-frames", "65"],
+                    "'max_pinned_frames' must be no greater than 'size_table'",
+                ),
+                (
+                    &["--hdd-rpm", "0"],
+                    "'hdd_rpm', 'hdd_bandwidth', and 'ssd_bandwidth' must be non-zero values",
+                ),
+                (
+                    &["--size-l1", "1000"],
+                    "cache sizes must be multiples of a power of 2 line size times the ways",
+                ),
+                (
+                    &["--frame-coloring", "bogus"],
+                    "'frame_coloring' must be one of 'none', 'page-coloring', or 'bin-hopping'",
+                ),
+                (
+                    &["--page-walk-levels", "0"],
+                    "'page_walk_levels' must be a non-zero value no greater than the page bits",
+                ),
+            ];
+            for (args, error) in rejected {
+                let config = Config::parse_from(["virtual_memory_sim"].iter().chain(args));
+                assert_eq!(config.check(), Err(error), "{:?}", args);
+            }
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use crate::config::Config;
+use crate::tracker::Tracker;
+use std::str::FromStr;
+
+/// The `DeviceKind` enum is the kind of block device modeled behind every backing store and swap
+/// space: a hard disk drive, whose requests pay for a seek and rotational latency, or a solid
+/// state drive, whose requests pay a fixed latency.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeviceKind {
+    Hdd,
+    Ssd,
+}
+
+impl FromStr for DeviceKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hdd" => Ok(Self::Hdd),
+            "ssd" => Ok(Self::Ssd),
+            _ => Err(format!("unknown device kind: '{}'", s)),
+        }
+    }
+}
+
+/// The `Device` struct models the time taken by the block device holding the backing stores and
+/// swap space, in nanoseconds of simulated time. Each request is charged a positioning time (the
+/// average seek plus half a rotation for a hard disk drive, or the fixed latency of a solid state
+/// drive) along with the time to transfer its data at the bandwidth of the device.
+///
+/// The device serves a single request at a time in the order submitted. A request submitted
+/// while the device is still busy, such as a page-in arriving behind page-outs issued earlier,
+/// waits in the queue until the requests before it complete.
+pub struct Device {
+    positioning_ns: usize,
+    bandwidth: usize,
+    busy_until: usize,
+    requests: usize,
+    wait_ns: usize,
+}
+
+impl Device {
+    /// Create a new, idle `Device` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - An instance of the configuration struct which contains the kind of device
+    ///   along with its latencies and bandwidth.
+    ///
+    /// # Panics
+    ///
+    /// The call will panic if the kind of device is unknown.
+    pub fn build(config: &Config) -> Self {
+        let kind: DeviceKind = config
+            .device
+            .parse()
+            .unwrap_or_else(|err| panic!("{}", err));
+        let (positioning_ns, bandwidth) = match kind {
+            DeviceKind::Hdd => (
+                config.hdd_seek_ns as usize + 30_000_000_000 / config.hdd_rpm as usize,
+                config.hdd_bandwidth as usize,
+            ),
+            DeviceKind::Ssd => (
+                config.ssd_latency_ns as usize,
+                config.ssd_bandwidth as usize,
+            ),
+        };
+        Self {
+            positioning_ns,
+            bandwidth,
+            busy_until: 0,
+            requests: 0,
+            wait_ns: 0,
+        }
+    }
+
+    /// Return the time taken to serve a request once it reaches the head of the queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - the number of bytes transferred by the request.
+    fn service_ns(&self, bytes: usize) -> usize {
+        // A bandwidth of one megabyte per second transfers one byte per microsecond.
+        self.positioning_ns + bytes * 1000 / self.bandwidth
+    }
+
+    /// Submit requests to the device at the provided time, returning the time at which the last
+    /// of them completes.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - the simulated time at which the requests are submitted.
+    /// * `requests` - the number of requests submitted.
+    /// * `bytes` - the number of bytes transferred by the requests altogether, divided evenly
+    ///   among them.
+    pub fn submit(&mut self, now: usize, requests: usize, bytes: usize) -> usize {
+        for _ in 0..requests {
+            let start = now.max(self.busy_until);
+            self.wait_ns += start - now;
+            self.busy_until = start + self.service_ns(bytes / requests);
+            self.requests += 1;
+        }
+        self.busy_until.max(now)
+    }
+
+    /// Copy the statistics of the device into the provided tracker.
+    ///
+    /// # Arguments
+    ///
+    /// * `tracker` - the tracker receiving the statistics.
+    pub fn record(&self, tracker: &mut Tracker) {
+        tracker.device_requests = self.requests;
+        tracker.device_wait_ns = self.wait_ns;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[cfg(test)]
+    mod device_tests {
+
+        use super::*;
+        use clap::Parser;
+
+        #[test]
+        fn device_kind() {
+            assert_eq!("hdd".parse(), Ok(DeviceKind::Hdd));
+            assert_eq!("ssd".parse(), Ok(DeviceKind::Ssd));
+            assert!("tape".parse::<DeviceKind>().is_err());
+        }
+
+        #[test]
+        fn submit() {
+            let mut config = Config::parse_from(["virtual_memory_sim"]);
+            config.device = String::from("hdd");
+            config.hdd_seek_ns = 4_000_000;
+            config.hdd_rpm = 7500;
+            config.hdd_bandwidth = 256;
+            let mut device = Device::build(&config);
+            assert_eq!(device.service_ns(256), 8_001_000);
+            assert_eq!(device.submit(1000, 2, 512), 16_003_000);
+            assert_eq!(device.submit(20_000_000, 1, 256), 28_001_000);
+
+            let mut tracker = Tracker::new();
+            device.record(&mut tracker);
+            assert_eq!(tracker.device_requests, 3);
+            assert_eq!(tracker.device_wait_ns, 8_001_000);
+
+            config.device = String::from("ssd");
+            config.ssd_latency_ns = 100_000;
+            config.ssd_bandwidth = 512;
+            assert_eq!(Device::build(&config).service_ns(4096), 108_000);
+        }
+    }
+}
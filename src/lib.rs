@@ -1,16 +1,24 @@
 pub mod address;
+pub mod cache;
 pub mod config;
+pub mod device;
+pub mod prefetch;
 pub mod storage;
+pub mod swap;
 pub mod tracker;
 pub mod validator;
 pub mod virtual_memory;
+pub mod vma;
+pub mod walk;
+pub mod zswap;
 
 use address::AddressReader;
 use config::Config;
 use indicatif::{ProgressBar, ProgressStyle};
-use std::{process, thread, time::Duration};
+use std::{fs, process};
+use storage::{FileStore, MemoryStore, MmapStore};
 use validator::ValidationReader;
-use virtual_memory::VirtualMemory;
+use virtual_memory::{Error, VirtualMemory};
 
 const MASK_PAGE: u32 = 0x0000FF00;
 const MASK_OFFSET: u32 = 0x000000FF;
@@ -28,27 +36,36 @@ impl Simulation {
     /// # Arguments
     ///
     /// * `config` - An instance of the configuration struct which contains settings for a given
-    /// run.
+    ///   run.
+    ///
+    /// # Panics
+    ///
+    /// The call will panic if the backing store cannot be opened.
     pub fn build(config: &Config) -> Self {
+        let filename = &config.file_storage;
+        let virtual_memory = match config.storage_kind.as_str() {
+            "memory" => VirtualMemory::build(config, MemoryStore::new(fs::read(filename).unwrap())),
+            "mmap" => VirtualMemory::build(config, MmapStore::build(filename)),
+            _ => VirtualMemory::build(config, FileStore::build(filename)),
+        };
         Self {
             address_reader: AddressReader::new(&config.file_address),
             validation_reader: ValidationReader::new(&config.file_validation),
-            virtual_memory: VirtualMemory::build(
-                config.size_tlb as usize,
-                config.size_table as usize,
-                config.size_frame as u64,
-                &config.file_storage,
-            ),
+            virtual_memory,
         }
     }
 }
 
 /// Using the configuration provided, run the simulation to completion while logging performance
 /// and error metrics along the way. Should any incorrect memory accesses occur due to cache
-/// incoherence, debugging information will be logged to STDERR.
+/// incoherence, debugging information will be logged to STDERR. Protection and segmentation faults,
+/// along with references to pages beyond the end of the backing store, are logged in the same
+/// manner and count against the number of correct accesses. Processes killed by the OOM killer are
+/// logged to STDERR as they occur, and the simulation stops early should the process replaying the
+/// trace be killed.
 ///
-/// Note that a delay may be added to each iteration if desired. See the definition of the
-/// configuration struct default values.
+/// Time is simulated rather than spent: each access advances a virtual clock by the configured
+/// latencies (see `VirtualMemory::perform`), from which the effective access time is reported.
 ///
 /// # Arguments
 ///
@@ -63,21 +80,39 @@ pub fn run_simulation(config: Config) {
     let num_records = AddressReader::new(&config.file_address).count() as u64;
     let pb = ProgressBar::new(num_records);
     pb.set_style(ProgressStyle::with_template("running simulation: {spinner}").unwrap());
-    for (i, (virtual_address, validation_entry)) in
-        address_reader.zip(validation_reader).enumerate()
+    for (i, (memory_access, validation_entry)) in address_reader.zip(validation_reader).enumerate()
     {
-        let access_result = virtual_memory.access(virtual_address).unwrap();
-        match access_result == validation_entry {
-            true => virtual_memory.tracker.correct_memory_accesses += 1,
-            false => {
+        let result = virtual_memory.perform(memory_access);
+        for event in virtual_memory.events.drain(..) {
+            eprintln!("event on record {i:05}: {event:?}");
+        }
+        match result {
+            Ok(access_result) if access_result == validation_entry => {
+                virtual_memory.tracker.correct_memory_accesses += 1
+            }
+            Ok(access_result) => {
                 eprintln!("failure occurred on record: {i:05}");
                 eprintln!("--------------------------------");
                 eprintln!("expected: {validation_entry:?}");
                 eprintln!("received: {access_result:?}");
             }
+            Err(
+                fault @ (Error::ProtectionFault { .. }
+                | Error::SegmentationFault { .. }
+                | Error::PageOutOfRange { .. }),
+            ) => {
+                eprintln!("fault occurred on record: {i:05}");
+                eprintln!("--------------------------------");
+                eprintln!("expected: {validation_entry:?}");
+                eprintln!("received: {fault:?}");
+            }
+            Err(Error::OutOfMemory { pid }) => {
+                eprintln!("process {pid} killed on record: {i:05}");
+                break;
+            }
+            Err(err) => panic!("error: {:?}", err),
         }
         pb.inc(1);
-        thread::sleep(Duration::from_micros(config.delay_us.into()));
     }
     println!("{}", virtual_memory.tracker);
     let tracker = &virtual_memory.tracker;
@@ -0,0 +1,236 @@
+use crate::virtual_memory::Pid;
+use linked_hash_map::LinkedHashMap;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// The `Prefetcher` trait is implemented by each of the policies which speculatively load pages
+/// ahead of their first reference. Prefetchers observe the stream of page faults and respond with
+/// the pages they expect to be referenced soon, while the decision of whether each page can be
+/// loaded at all (e.g. whether it is already resident) remains with the caller.
+pub trait Prefetcher {
+    /// Observe a page fault and return the logical page numbers which should be prefetched in
+    /// response, in the order they should be loaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `pid` - the process which faulted.
+    /// * `page_number` - logical page number of the fault.
+    fn on_fault(&mut self, pid: Pid, page_number: usize) -> Vec<usize>;
+}
+
+/// The `ReadAhead` struct implements sequential read-ahead with an adaptive window, similar in
+/// spirit to the readahead performed by Linux. A fault on page N prefetches the pages following
+/// it. Should the next fault of the same process land exactly where the previous window ended (or
+/// immediately after the previous fault), the access pattern is considered sequential and the
+/// window doubles up to its maximum size. Any other fault resets the window to its initial size.
+pub struct ReadAhead {
+    initial_window: usize,
+    max_window: usize,
+    window: usize,
+    last: Option<(Pid, usize, usize)>,
+}
+
+impl ReadAhead {
+    /// Create a new `ReadAhead` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_window` - the largest number of pages prefetched following a single fault.
+    pub fn build(max_window: usize) -> Self {
+        let initial_window = max_window.min(2);
+        Self {
+            initial_window,
+            max_window,
+            window: initial_window,
+            last: None,
+        }
+    }
+}
+
+impl Prefetcher for ReadAhead {
+    fn on_fault(&mut self, pid: Pid, page_number: usize) -> Vec<usize> {
+        let sequential = matches!(
+            self.last,
+            Some((last_pid, last_page, window_end))
+                if last_pid == pid && (page_number == last_page + 1 || page_number == window_end)
+        );
+        self.window = match sequential {
+            true => (self.window * 2).min(self.max_window),
+            false => self.initial_window,
+        };
+        let window_end = page_number + 1 + self.window;
+        self.last = Some((pid, page_number, window_end));
+        (page_number + 1..window_end).collect()
+    }
+}
+
+/// The `Stride` struct implements a stride detector over the fault stream of each process. The
+/// distance between consecutive faults is remembered, and once the same non-zero distance is seen
+/// twice in a row the next `degree` pages along that stride are prefetched. Strides may be
+/// negative, in which case the pages below the fault are prefetched.
+pub struct Stride {
+    degree: usize,
+    history: HashMap<Pid, (usize, isize)>,
+}
+
+impl Stride {
+    /// Create a new `Stride` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `degree` - the number of pages prefetched once a stride is confirmed.
+    pub fn build(degree: usize) -> Self {
+        Self {
+            degree,
+            history: HashMap::new(),
+        }
+    }
+}
+
+impl Prefetcher for Stride {
+    fn on_fault(&mut self, pid: Pid, page_number: usize) -> Vec<usize> {
+        let stride = match self.history.get(&pid) {
+            Some((last_page, _)) => page_number as isize - *last_page as isize,
+            None => 0,
+        };
+        let confirmed = matches!(self.history.get(&pid), Some((_, last)) if *last == stride);
+        self.history.insert(pid, (page_number, stride));
+        if !confirmed || stride == 0 {
+            return Vec::new();
+        }
+        (1..=self.degree as isize)
+            .map_while(|step| page_number.checked_add_signed(step * stride))
+            .collect()
+    }
+}
+
+/// The `Markov` struct implements a correlation (Markov) prefetcher. For each faulting page, a
+/// table records which pages faulted immediately afterwards and how often. On a fault, the
+/// successors observed most frequently are prefetched, up to `degree` of them. The table holds at
+/// most `table_size` pages and victimizes the least recently faulted page when full, while each
+/// page remembers at most `degree` successors, replacing the least frequent.
+pub struct Markov {
+    degree: usize,
+    table_size: usize,
+    table: LinkedHashMap<(Pid, usize), Vec<(usize, usize)>>,
+    last: HashMap<Pid, usize>,
+}
+
+impl Markov {
+    /// Create a new `Markov` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `degree` - the number of successors remembered, and prefetched, per page.
+    /// * `table_size` - the number of pages tracked by the correlation table.
+    pub fn build(degree: usize, table_size: usize) -> Self {
+        Self {
+            degree,
+            table_size,
+            table: LinkedHashMap::with_capacity(table_size),
+            last: HashMap::new(),
+        }
+    }
+
+    /// Record that `successor` faulted immediately after `page_number`.
+    fn record(&mut self, pid: Pid, page_number: usize, successor: usize) {
+        let key = (pid, page_number);
+        if !self.table.contains_key(&key) && self.table.len() == self.table_size {
+            self.table.pop_front();
+        }
+        let successors = self.table.entry(key).or_default();
+        match successors.iter().position(|(page, _)| *page == successor) {
+            Some(index) => successors[index].1 += 1,
+            None if successors.len() < self.degree => successors.push((successor, 1)),
+            None => *successors.last_mut().expect("degree should be non-zero") = (successor, 1),
+        }
+        successors.sort_by_key(|(_, count)| Reverse(*count));
+    }
+}
+
+impl Prefetcher for Markov {
+    fn on_fault(&mut self, pid: Pid, page_number: usize) -> Vec<usize> {
+        if let Some(last_page) = self.last.insert(pid, page_number) {
+            self.record(pid, last_page, page_number);
+        }
+        match self.table.get_refresh(&(pid, page_number)) {
+            Some(successors) => successors.iter().map(|(page, _)| *page).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[cfg(test)]
+    mod read_ahead_tests {
+
+        use super::*;
+
+        #[test]
+        fn adaptive_window() {
+            let mut prefetcher = ReadAhead::build(8);
+            assert_eq!(prefetcher.on_fault(0, 10), vec![11, 12]);
+            assert_eq!(prefetcher.on_fault(0, 13), vec![14, 15, 16, 17]);
+            assert_eq!(prefetcher.on_fault(0, 18).len(), 8);
+            assert_eq!(prefetcher.on_fault(0, 27).len(), 8);
+            assert_eq!(prefetcher.on_fault(1, 36), vec![37, 38]);
+            assert_eq!(prefetcher.on_fault(1, 90), vec![91, 92]);
+        }
+    }
+
+    #[cfg(test)]
+    mod stride_tests {
+
+        use super::*;
+
+        #[test]
+        fn confirmed_stride() {
+            let mut prefetcher = Stride::build(3);
+            assert!(prefetcher.on_fault(0, 10).is_empty());
+            assert!(prefetcher.on_fault(0, 14).is_empty());
+            assert_eq!(prefetcher.on_fault(0, 18), vec![22, 26, 30]);
+            assert!(prefetcher.on_fault(1, 4).is_empty());
+            assert!(prefetcher.on_fault(1, 2).is_empty());
+            assert_eq!(prefetcher.on_fault(1, 0), Vec::<usize>::new());
+            assert!(prefetcher.on_fault(0, 19).is_empty());
+        }
+
+        #[test]
+        fn negative_stride() {
+            let mut prefetcher = Stride::build(2);
+            prefetcher.on_fault(0, 30);
+            prefetcher.on_fault(0, 20);
+            assert_eq!(prefetcher.on_fault(0, 10), vec![0]);
+        }
+    }
+
+    #[cfg(test)]
+    mod markov_tests {
+
+        use super::*;
+
+        #[test]
+        fn correlated_faults() {
+            let mut prefetcher = Markov::build(2, 8);
+            for page in [1, 5, 1, 7, 1, 5] {
+                prefetcher.on_fault(0, page);
+            }
+            assert_eq!(prefetcher.on_fault(0, 1), vec![5, 7]);
+            assert_eq!(prefetcher.on_fault(1, 1), Vec::<usize>::new());
+        }
+
+        #[test]
+        fn replacement() {
+            let mut prefetcher = Markov::build(1, 2);
+            for page in [1, 2, 3, 1] {
+                prefetcher.on_fault(0, page);
+            }
+            assert!(!prefetcher.table.contains_key(&(0, 1)));
+            assert_eq!(prefetcher.on_fault(0, 3), vec![1]);
+        }
+    }
+}
@@ -1,15 +1,91 @@
+use memmap2::Mmap;
 use std::fs::File;
-use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::io::{self, BufReader, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::str::FromStr;
 
-/// The `Storage` struct is a simply utility wrapper around the Rust standard library's `BufReader`
-/// API. Instances of the structure are used to perform random reads on a backing store binary
-/// file. Relative to the context of the project, this struct is used to read chunks of data from a
-/// backing storage pool. Conceptually, this can be anything along the lines of actual file data,
-/// swap space, or program instructions that have yet to be paged in.
-pub struct Storage(BufReader<File>);
+/// The `BackingStore` trait describes a store of fixed-size chunks of data which may be read and
+/// written at random. Relative to the context of the project, implementations are used to read
+/// pages from a backing storage pool. Conceptually, this can be anything along the lines of actual
+/// file data, swap space, or program instructions that have yet to be paged in.
+///
+/// Chunk positions are expressed as a `seek_multiplier`: the number of times the buffer size is
+/// multiplied to obtain the start position of the operation.
+pub trait BackingStore {
+    /// Read the chunk of data found at the position determined by `seek_multiplier` into the
+    /// buffer passed by mutable reference. Reads are exact: the entire buffer is filled or an
+    /// error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `seek_multiplier` - the number of times the buffer size is multiplied to obtain the start
+    ///   position for the read operation.
+    /// * `buffer` - a mutable reference to a buffer for data to be read into.
+    ///
+    /// # Errors
+    ///
+    /// An `UnexpectedEof` error is returned if the chunk extends past the end of the store.
+    fn read(&mut self, seek_multiplier: u64, buffer: &mut [u8]) -> Result<(), io::Error> {
+        self.read_at(buffer.len() as u64 * seek_multiplier, buffer)
+    }
+
+    /// Read data beginning at an arbitrary byte position into the buffer, filling it entirely.
+    /// Unlike `read`, the position need not be a multiple of the buffer size, allowing several
+    /// consecutive chunks to be read in a single operation.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - the byte position at which the read begins.
+    /// * `buffer` - a mutable reference to a buffer for data to be read into.
+    ///
+    /// # Errors
+    ///
+    /// An `UnexpectedEof` error is returned if the data extends past the end of the store.
+    fn read_at(&mut self, position: u64, buffer: &mut [u8]) -> Result<(), io::Error>;
+
+    /// Write the entire buffer at the position determined by `seek_multiplier`, allowing a chunk
+    /// written here to be read back later with the same `seek_multiplier`.
+    ///
+    /// # Arguments
+    ///
+    /// * `seek_multiplier` - the number of times the buffer size is multiplied to obtain the start
+    ///   position for the write operation.
+    /// * `buffer` - the data to be written.
+    fn write(&mut self, seek_multiplier: u64, buffer: &[u8]) -> Result<(), io::Error>;
+
+    /// Return the size of the store in bytes.
+    fn size(&self) -> u64;
+}
+
+/// The `OutOfRange` enum is the policy applied when a page lying beyond the end of a store is
+/// referenced. The page may be reported as an error, filled with zeroes, or wrapped around to the
+/// start of the store (the page number modulo the number of whole pages in the store).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutOfRange {
+    Error,
+    ZeroFill,
+    Wrap,
+}
 
-impl Storage {
-    /// Create a new instance of the `Storage` struct.
+impl FromStr for OutOfRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "error" => Ok(Self::Error),
+            "zero-fill" => Ok(Self::ZeroFill),
+            "wrap" => Ok(Self::Wrap),
+            _ => Err(format!("unknown out of range policy: '{}'", s)),
+        }
+    }
+}
+
+/// The `FileStore` struct is a simply utility wrapper around the Rust standard library's
+/// `BufReader` API. Instances of the structure are used to perform random reads on a backing store
+/// binary file.
+pub struct FileStore(BufReader<File>);
+
+impl FileStore {
+    /// Create a new instance of the `FileStore` struct.
     ///
     /// # Panics
     /// The call will panic if the filepath provided does not exist.
@@ -18,31 +94,181 @@ impl Storage {
         Self(BufReader::new(file))
     }
 
+    /// Create a new instance of the `FileStore` struct which is both readable and writable. Any
+    /// existing file at the provided path is truncated so each simulation begins with an empty
+    /// store. Such instances are used for swap space, which receives modified pages when they are
+    /// paged out.
+    ///
+    /// # Panics
+    /// The call will panic if the file cannot be created.
+    pub fn build_swap(filename: &str) -> Self {
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(filename)
+            .unwrap();
+        Self(BufReader::new(file))
+    }
+
+    /// Create a new instance of the `FileStore` struct which is both readable and writable while
+    /// preserving the existing contents of the file. Such instances back shared file mappings,
+    /// which write modified pages back to the file they were read from.
+    ///
+    /// # Panics
+    /// The call will panic if the filepath provided does not exist.
+    pub fn build_writable(filename: &str) -> Self {
+        let file = File::options()
+            .read(true)
+            .write(true)
+            .open(filename)
+            .unwrap();
+        Self(BufReader::new(file))
+    }
+}
+
+impl BackingStore for FileStore {
     /// Seeks to a position in the backing store and reads a chunk of data into the the buffer
     /// passed by mutable reference. A `Result` is returned to indicate the success of the
     /// operation.
     ///
-    /// The seek position is determined by the value obtained from `seek_multipler * buffer.len()`.
-    ///
     /// # Arguments
     ///
-    /// * `seek_multiplier` - the number of times the buffer size is multiplied to obtain the start
-    /// position for the read operation.
+    /// * `position` - the byte position at which the read begins.
     /// * `buffer` - a mutable reference to a buffer for data to be read into.
     ///
     /// # Errors
     ///
-    /// An error may occur if:
-    /// - the buffer is not a valid size
-    /// - the seek_multiplier is improperly set
+    /// An `UnexpectedEof` error is returned if the buffer cannot be filled entirely, which is the
+    /// result of attempting to read past the bounds of the backing store.
+    fn read_at(&mut self, position: u64, buffer: &mut [u8]) -> Result<(), io::Error> {
+        self.0.seek(SeekFrom::Start(position))?;
+        self.0.read_exact(buffer)
+    }
+
+    /// Seeks to a position in the store and writes the entire buffer there. The seek position is
+    /// determined in the same manner as `read`, allowing a frame written out here to be read back
+    /// later with the same `seek_multiplier`.
+    ///
+    /// # Arguments
+    ///
+    /// * `seek_multiplier` - the number of times the buffer size is multiplied to obtain the start
+    ///   position for the write operation.
+    /// * `buffer` - the data to be written.
     ///
-    /// Such errors are the result of attempting to read past the bounds of the backing store.
-    pub fn read(&mut self, seek_multiplier: u64, buffer: &mut Vec<u8>) -> Result<(), io::Error> {
+    /// # Errors
+    ///
+    /// An error will occur if the store was opened read-only (see `build`) or the underlying write
+    /// fails.
+    fn write(&mut self, seek_multiplier: u64, buffer: &[u8]) -> Result<(), io::Error> {
         let seek_pos = SeekFrom::Start(buffer.len() as u64 * seek_multiplier);
         self.0.seek(seek_pos)?;
-        self.0.read(buffer)?;
+        self.0.get_mut().write_all(buffer)
+    }
+
+    fn size(&self) -> u64 {
+        self.0
+            .get_ref()
+            .metadata()
+            .map_or(0, |metadata| metadata.len())
+    }
+}
+
+/// The `MemoryStore` struct holds the entire store in memory. Instances are convenient in tests
+/// and for small simulated stores, since they need not exist on disk. Writes past the end of the
+/// store extend it.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryStore(Vec<u8>);
+
+impl MemoryStore {
+    /// Create a new instance of the `MemoryStore` struct holding the provided contents.
+    ///
+    /// # Arguments
+    ///
+    /// * `contents` - the initial bytes of the store.
+    pub fn new(contents: Vec<u8>) -> Self {
+        Self(contents)
+    }
+}
+
+impl BackingStore for MemoryStore {
+    /// Copy the data found at the provided position into the buffer.
+    fn read_at(&mut self, position: u64, buffer: &mut [u8]) -> Result<(), io::Error> {
+        read_chunk(&self.0, position, buffer)
+    }
+
+    /// Copy the buffer into the store at the position determined by `seek_multiplier`.
+    fn write(&mut self, seek_multiplier: u64, buffer: &[u8]) -> Result<(), io::Error> {
+        let start = buffer.len() * seek_multiplier as usize;
+        let end = start + buffer.len();
+        if self.0.len() < end {
+            self.0.resize(end, 0);
+        }
+        self.0[start..end].copy_from_slice(buffer);
         Ok(())
     }
+
+    fn size(&self) -> u64 {
+        self.0.len() as u64
+    }
+}
+
+/// The `MmapStore` struct maps a file into the address space of the simulator itself, allowing
+/// pages to be copied out without a system call per read. The mapping is read-only.
+pub struct MmapStore(Mmap);
+
+impl MmapStore {
+    /// Create a new instance of the `MmapStore` struct.
+    ///
+    /// # Panics
+    /// The call will panic if the filepath provided does not exist or cannot be mapped.
+    pub fn build(filename: &str) -> Self {
+        let file = File::open(filename).unwrap();
+        // SAFETY: the simulator never modifies the file while it is mapped.
+        Self(unsafe { Mmap::map(&file) }.unwrap())
+    }
+}
+
+impl BackingStore for MmapStore {
+    /// Copy the data found at the provided position into the buffer.
+    fn read_at(&mut self, position: u64, buffer: &mut [u8]) -> Result<(), io::Error> {
+        read_chunk(&self.0, position, buffer)
+    }
+
+    /// # Errors
+    ///
+    /// Memory-mapped stores are read-only, so a `PermissionDenied` error is always returned.
+    fn write(&mut self, _seek_multiplier: u64, _buffer: &[u8]) -> Result<(), io::Error> {
+        Err(io::Error::new(
+            ErrorKind::PermissionDenied,
+            "memory-mapped stores are read-only",
+        ))
+    }
+
+    fn size(&self) -> u64 {
+        self.0.len() as u64
+    }
+}
+
+/// Copy the data of `contents` found at the provided position into the buffer, failing in the
+/// same manner as an exact file read should the data extend past the end of `contents`.
+///
+/// # Arguments
+///
+/// * `contents` - the entire contents of an in-memory store.
+/// * `start` - the byte position at which the read begins.
+/// * `buffer` - a mutable reference to a buffer for data to be read into.
+fn read_chunk(contents: &[u8], start: u64, buffer: &mut [u8]) -> Result<(), io::Error> {
+    let end = start + buffer.len() as u64;
+    if end > contents.len() as u64 {
+        return Err(io::Error::new(
+            ErrorKind::UnexpectedEof,
+            "read past the end of the store",
+        ));
+    }
+    buffer.copy_from_slice(&contents[start as usize..end as usize]);
+    Ok(())
 }
 
 #[cfg(test)]
@@ -56,9 +282,9 @@ mod test {
     #[cfg(test)]
     mod storage_tests {
 
-        fn standard_storage() -> Storage {
-            let config = Config::parse();
-            Storage::build(&config.file_storage)
+        fn standard_storage() -> FileStore {
+            let config = Config::parse_from(["virtual_memory_sim"]);
+            FileStore::build(&config.file_storage)
         }
 
         use super::*;
@@ -66,11 +292,65 @@ mod test {
         #[test]
         fn read() {
             let mut store = standard_storage();
-            let mut buffer = vec![0 as u8; 256];
+            let mut buffer = vec![0_u8; 256];
             store.read(0, &mut buffer).unwrap();
             assert_eq!(buffer[7], 0x01);
             assert_eq!(buffer[11], 0x02);
             assert_eq!(buffer[15], 0x03);
         }
+
+        #[test]
+        fn write() {
+            let filename = std::env::temp_dir()
+                .join(format!("storage_tests_write_{}.bin", std::process::id()));
+            let mut store = FileStore::build_swap(filename.to_str().unwrap());
+            let mut buffer = vec![7_u8; 16];
+            store.write(2, &buffer).unwrap();
+            buffer.fill(0);
+            store.read(2, &mut buffer).unwrap();
+            assert!(buffer.iter().all(|x| *x == 7));
+            assert!(standard_storage().write(0, &buffer).is_err());
+            assert_eq!(store.size(), 48);
+            assert_eq!(
+                store.read(3, &mut buffer).unwrap_err().kind(),
+                ErrorKind::UnexpectedEof
+            );
+        }
+
+        #[test]
+        fn memory_store() {
+            let mut store = MemoryStore::new(vec![1_u8; 16]);
+            let mut buffer = vec![0_u8; 8];
+            store.write(2, &[2_u8; 8]).unwrap();
+            store.read(1, &mut buffer).unwrap();
+            assert!(buffer.iter().all(|x| *x == 1));
+            store.read(2, &mut buffer).unwrap();
+            assert!(buffer.iter().all(|x| *x == 2));
+            assert_eq!(store.size(), 24);
+            assert!(store.read(3, &mut buffer).is_err());
+            store.read_at(12, &mut buffer).unwrap();
+            assert_eq!(buffer[..4], [1, 1, 1, 1]);
+            assert_eq!(buffer[4..], [2, 2, 2, 2]);
+        }
+
+        #[test]
+        fn mmap_store() {
+            let config = Config::parse_from(["virtual_memory_sim"]);
+            let mut store = MmapStore::build(&config.file_storage);
+            let mut buffer = vec![0_u8; 256];
+            let mut expected = vec![0_u8; 256];
+            store.read(3, &mut buffer).unwrap();
+            standard_storage().read(3, &mut expected).unwrap();
+            assert_eq!(buffer, expected);
+            assert!(store.write(0, &buffer).is_err());
+            assert_eq!(store.size(), standard_storage().size());
+        }
+
+        #[test]
+        fn out_of_range() {
+            assert_eq!("wrap".parse(), Ok(OutOfRange::Wrap));
+            assert_eq!("zero-fill".parse(), Ok(OutOfRange::ZeroFill));
+            assert!("ignore".parse::<OutOfRange>().is_err());
+        }
     }
 }
@@ -0,0 +1,200 @@
+use crate::storage::BackingStore;
+use crate::tracker::Tracker;
+use crate::zswap::CompressedPool;
+use std::io;
+
+/// The `SwapSpace` struct manages a swap area divided into page-sized slots, kept separate from
+/// the read-only backing store. Modified pages are assigned a slot when they are first paged out
+/// and keep it until the page itself is freed, at which point the slot returns to the pool. Slots
+/// are allocated first-fit, so freeing slots in an arbitrary order leaves the free space scattered
+/// across the area (see `largest_free_extent`).
+///
+/// The slots are stored using any `BackingStore`, with a slot number serving directly as the seek
+/// multiplier of each read and write. Writes are first offered to a pool of compressed pages held
+/// in memory (see `CompressedPool`), and only reach the store should the pool decline them or
+/// later evict them, so `reads` and `writes` count accesses to the store alone.
+pub struct SwapSpace {
+    store: Box<dyn BackingStore>,
+    pool: CompressedPool,
+    slots: Vec<bool>,
+    in_use: usize,
+    peak: usize,
+    reads: usize,
+    writes: usize,
+}
+
+impl SwapSpace {
+    /// Create a new `SwapSpace` instance with every slot free.
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - the store holding the contents of the slots.
+    /// * `capacity` - the number of slots in the swap area.
+    /// * `size_pool` - the number of compressed bytes the pool may hold, with zero disabling it.
+    pub fn build<S: BackingStore + 'static>(store: S, capacity: usize, size_pool: usize) -> Self {
+        Self {
+            store: Box::new(store),
+            pool: CompressedPool::build(size_pool),
+            slots: vec![false; capacity],
+            in_use: 0,
+            peak: 0,
+            reads: 0,
+            writes: 0,
+        }
+    }
+
+    /// Reserve the lowest-numbered free slot and return it, or `None` should the swap area be
+    /// full.
+    pub fn allocate(&mut self) -> Option<usize> {
+        let slot = self.slots.iter().position(|used| !used)?;
+        self.slots[slot] = true;
+        self.in_use += 1;
+        self.peak = self.peak.max(self.in_use);
+        Some(slot)
+    }
+
+    /// Return a slot to the pool of free slots. Freeing a slot which is already free has no
+    /// effect.
+    ///
+    /// # Arguments
+    ///
+    /// * `slot` - the slot to be freed.
+    pub fn free(&mut self, slot: usize) {
+        if std::mem::replace(&mut self.slots[slot], false) {
+            self.in_use -= 1;
+        }
+        self.pool.remove(slot);
+    }
+
+    /// Return whether the contents of a slot are held by the compressed pool, such that reading the
+    /// slot requires no access to the store.
+    ///
+    /// # Arguments
+    ///
+    /// * `slot` - the slot to be read.
+    pub fn compressed(&self, slot: usize) -> bool {
+        self.pool.contains(slot)
+    }
+
+    /// Read the contents of a slot into the buffer, from the compressed pool should it hold them.
+    ///
+    /// # Arguments
+    ///
+    /// * `slot` - the slot to be read.
+    /// * `buffer` - a mutable reference to a buffer for data to be read into.
+    ///
+    /// # Errors
+    ///
+    /// See `BackingStore::read`.
+    pub fn read(&mut self, slot: usize, buffer: &mut [u8]) -> Result<(), io::Error> {
+        if self.pool.load(slot, buffer) {
+            return Ok(());
+        }
+        self.reads += 1;
+        self.store.read(slot as u64, buffer)
+    }
+
+    /// Write the buffer to a slot. The buffer is stored in the compressed pool should it accept
+    /// it, in which case any pages the pool evicts are written to the store instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `slot` - the slot to be written.
+    /// * `buffer` - the data to be written.
+    ///
+    /// # Errors
+    ///
+    /// See `BackingStore::write`.
+    pub fn write(&mut self, slot: usize, buffer: &[u8]) -> Result<(), io::Error> {
+        if let Some(evicted) = self.pool.store(slot, buffer) {
+            for (slot, contents) in evicted {
+                self.writes += 1;
+                self.store.write(slot as u64, &contents)?;
+            }
+            return Ok(());
+        }
+        self.writes += 1;
+        self.store.write(slot as u64, buffer)
+    }
+
+    /// Return the number of free slots.
+    pub fn available(&self) -> usize {
+        self.slots.len() - self.in_use
+    }
+
+    /// Return the number of reads and writes made to the store, excluding those served by the
+    /// compressed pool.
+    pub fn transfers(&self) -> usize {
+        self.reads + self.writes
+    }
+
+    /// Return the length of the longest run of consecutive free slots.
+    pub fn largest_free_extent(&self) -> usize {
+        self.slots
+            .split(|used| *used)
+            .map(|extent| extent.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Copy the statistics of the swap area into the provided tracker.
+    ///
+    /// # Arguments
+    ///
+    /// * `tracker` - the tracker receiving the statistics.
+    pub fn record(&self, tracker: &mut Tracker) {
+        tracker.swap_capacity = self.slots.len();
+        tracker.swap_slots_in_use = self.in_use;
+        tracker.swap_slots_peak = self.peak;
+        tracker.swap_largest_free_extent = self.largest_free_extent();
+        tracker.swap_reads = self.reads;
+        tracker.swap_writes = self.writes;
+        self.pool.record(tracker);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[cfg(test)]
+    mod swap_space_tests {
+
+        use super::*;
+        use crate::storage::MemoryStore;
+
+        #[test]
+        fn allocate_and_free() {
+            let mut swap = SwapSpace::build(MemoryStore::default(), 4, 0);
+            assert_eq!(
+                (0..5).map(|_| swap.allocate()).collect::<Vec<_>>(),
+                vec![Some(0), Some(1), Some(2), Some(3), None]
+            );
+            swap.free(1);
+            swap.free(3);
+            swap.free(3);
+            assert_eq!(swap.largest_free_extent(), 1);
+            assert_eq!(swap.allocate(), Some(1));
+
+            let mut tracker = Tracker::new();
+            swap.record(&mut tracker);
+            assert_eq!(tracker.swap_slots_in_use, 3);
+            assert_eq!(tracker.swap_slots_peak, 4);
+        }
+
+        #[test]
+        fn read_and_write() {
+            let mut swap = SwapSpace::build(MemoryStore::default(), 4, 0);
+            let mut buffer = vec![3_u8; 8];
+            swap.write(2, &buffer).unwrap();
+            buffer.fill(0);
+            swap.read(2, &mut buffer).unwrap();
+            assert!(buffer.iter().all(|x| *x == 3));
+
+            let mut tracker = Tracker::new();
+            swap.record(&mut tracker);
+            assert_eq!((tracker.swap_reads, tracker.swap_writes), (1, 1));
+        }
+    }
+}
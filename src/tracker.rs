@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 /// The `Tracker` struct is a simple collection of named performance data counters used for
 /// collecting data points on the simulation. The data collected is used to conduct light
 /// statistical analysis about the performance of an algorithm.
@@ -8,6 +10,71 @@ pub struct Tracker {
     pub tlb_flushes: usize,
     pub attempted_memory_accesses: usize,
     pub correct_memory_accesses: usize,
+    pub page_walks: usize,
+    pub walk_references: usize,
+    pub walk_references_saved: usize,
+    pub pwc_hits: Vec<usize>,
+    pub write_accesses: usize,
+    pub page_outs: usize,
+    pub swap_ins: usize,
+    pub read_protection_faults: usize,
+    pub write_protection_faults: usize,
+    pub execute_protection_faults: usize,
+    pub forks: usize,
+    pub cow_frames_shared: usize,
+    pub cow_copies: usize,
+    pub minor_faults: usize,
+    pub rmap_invalidations: usize,
+    pub segmentation_faults: usize,
+    pub zero_fill_faults: usize,
+    pub stack_growths: usize,
+    pub file_write_backs: usize,
+    pub out_of_range_pages: usize,
+    pub swap_reads: usize,
+    pub swap_writes: usize,
+    pub swap_slots_in_use: usize,
+    pub swap_slots_peak: usize,
+    pub swap_capacity: usize,
+    pub swap_largest_free_extent: usize,
+    pub page_faults: usize,
+    pub prefetches: usize,
+    pub prefetch_hits: usize,
+    pub prefetch_wasted: usize,
+    pub prefetcher: String,
+    pub fault_around_pages: usize,
+    pub fault_around_io_saved: usize,
+    pub direct_reclaims: usize,
+    pub background_reclaims: usize,
+    pub file_reads: usize,
+    pub soft_faults: usize,
+    pub hard_faults: usize,
+    pub thp_promotions: usize,
+    pub thp_demotions: usize,
+    pub huge_fragmentation: usize,
+    pub pinned_frames: usize,
+    pub oom_kills: usize,
+    pub ksm_merges: usize,
+    pub ksm_unmerges: usize,
+    pub ksm_frames_saved: usize,
+    pub zswap_stores: usize,
+    pub zswap_faults: usize,
+    pub zswap_hits: usize,
+    pub zswap_writebacks: usize,
+    pub zswap_original_bytes: usize,
+    pub zswap_compressed_bytes: usize,
+    pub simulated_time_ns: usize,
+    pub device_requests: usize,
+    pub device_wait_ns: usize,
+    pub fault_service_ns: usize,
+    pub l1_hits: usize,
+    pub l1_misses: usize,
+    pub l2_hits: usize,
+    pub l2_misses: usize,
+    pub l1_conflict_misses: usize,
+    pub l2_conflict_misses: usize,
+    pub frame_coloring: String,
+    pub tlb_hits_by_size: BTreeMap<usize, usize>,
+    pub tlb_reach_by_size: BTreeMap<usize, usize>,
 }
 
 impl Tracker {
@@ -19,8 +86,95 @@ impl Tracker {
             tlb_flushes: 0,
             attempted_memory_accesses: 0,
             correct_memory_accesses: 0,
+            page_walks: 0,
+            walk_references: 0,
+            walk_references_saved: 0,
+            pwc_hits: Vec::new(),
+            write_accesses: 0,
+            page_outs: 0,
+            swap_ins: 0,
+            read_protection_faults: 0,
+            write_protection_faults: 0,
+            execute_protection_faults: 0,
+            forks: 0,
+            cow_frames_shared: 0,
+            cow_copies: 0,
+            minor_faults: 0,
+            rmap_invalidations: 0,
+            segmentation_faults: 0,
+            zero_fill_faults: 0,
+            stack_growths: 0,
+            file_write_backs: 0,
+            out_of_range_pages: 0,
+            swap_reads: 0,
+            swap_writes: 0,
+            swap_slots_in_use: 0,
+            swap_slots_peak: 0,
+            swap_capacity: 0,
+            swap_largest_free_extent: 0,
+            page_faults: 0,
+            prefetches: 0,
+            prefetch_hits: 0,
+            prefetch_wasted: 0,
+            prefetcher: String::from("none"),
+            fault_around_pages: 0,
+            fault_around_io_saved: 0,
+            direct_reclaims: 0,
+            background_reclaims: 0,
+            file_reads: 0,
+            soft_faults: 0,
+            hard_faults: 0,
+            thp_promotions: 0,
+            thp_demotions: 0,
+            huge_fragmentation: 0,
+            pinned_frames: 0,
+            oom_kills: 0,
+            ksm_merges: 0,
+            ksm_unmerges: 0,
+            ksm_frames_saved: 0,
+            zswap_stores: 0,
+            zswap_faults: 0,
+            zswap_hits: 0,
+            zswap_writebacks: 0,
+            zswap_original_bytes: 0,
+            zswap_compressed_bytes: 0,
+            simulated_time_ns: 0,
+            device_requests: 0,
+            device_wait_ns: 0,
+            fault_service_ns: 0,
+            l1_hits: 0,
+            l1_misses: 0,
+            l2_hits: 0,
+            l2_misses: 0,
+            l1_conflict_misses: 0,
+            l2_conflict_misses: 0,
+            frame_coloring: String::from("none"),
+            tlb_hits_by_size: BTreeMap::new(),
+            tlb_reach_by_size: BTreeMap::new(),
+        }
+    }
+
+    /// Return the external fragmentation of swap space: the share of free slots lying outside the
+    /// largest run of consecutive free slots. Zero is returned when no slots are free.
+    pub fn swap_fragmentation(&self) -> f32 {
+        let free = self.swap_capacity - self.swap_slots_in_use;
+        match free {
+            0 => 0.0,
+            free => 1.0 - self.swap_largest_free_extent as f32 / free as f32,
         }
     }
+
+    /// Return the effective access time in nanoseconds: the simulated time spent per attempted
+    /// memory access.
+    pub fn effective_access_time(&self) -> f64 {
+        self.simulated_time_ns as f64 / self.attempted_memory_accesses as f64
+    }
+}
+
+impl Default for Tracker {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl std::fmt::Display for Tracker {
@@ -30,7 +184,7 @@ impl std::fmt::Display for Tracker {
     /// # Arguments
     ///
     /// * `f` - A mutable reference to a standard library formatter instance. For most use cases,
-    /// this is provided automatically as this method is not meant to be called directly.
+    ///   this is provided automatically as this method is not meant to be called directly.
     ///
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -43,18 +197,179 @@ tlb_hits:                 {:08}
 tlb_flushes:              {:08}
 attempted_memory_acceses: {:08}
 correct_memory_accesses:  {:08}
-
-
-tlb hit ratio:            {:.06}
-page hit ratio:           {:.06}
-               ",
+page_walks:               {:08}
+walk_references:          {:08}
+walk_references_saved:    {:08}
+write_accesses:           {:08}
+page_outs:                {:08}
+swap_ins:                 {:08}
+read_protection_faults:   {:08}
+write_protection_faults:  {:08}
+exec_protection_faults:   {:08}
+forks:                    {:08}
+cow_frames_shared:        {:08}
+cow_copies:               {:08}
+cow_frames_saved:         {:08}
+minor_faults:             {:08}
+rmap_invalidations:       {:08}
+segmentation_faults:      {:08}
+zero_fill_faults:         {:08}
+stack_growths:            {:08}
+file_write_backs:         {:08}
+out_of_range_pages:       {:08}
+swap_reads:               {:08}
+swap_writes:              {:08}
+swap_slots_in_use:        {:08}
+swap_slots_peak:          {:08}
+page_faults:              {:08}
+prefetches:               {:08}
+prefetch_hits:            {:08}
+prefetch_wasted:          {:08}
+fault_around_pages:       {:08}
+fault_around_io_saved:    {:08}
+direct_reclaims:          {:08}
+background_reclaims:      {:08}
+file_reads:               {:08}
+soft_faults:              {:08}
+hard_faults:              {:08}
+thp_promotions:           {:08}
+thp_demotions:            {:08}
+huge_fragmentation:       {:08}
+pinned_frames:            {:08}
+oom_kills:                {:08}
+ksm_merges:               {:08}
+ksm_unmerges:             {:08}
+ksm_frames_saved:         {:08}
+zswap_stores:             {:08}
+zswap_faults:             {:08}
+zswap_hits:               {:08}
+zswap_writebacks:         {:08}
+zswap_original_bytes:     {:08}
+zswap_compressed_bytes:   {:08}
+simulated_time_ns:        {:08}
+device_requests:          {:08}
+device_wait_ns:           {:08}
+fault_service_ns:         {:08}
+l1_hits:                  {:08}
+l1_misses:                {:08}
+l2_hits:                  {:08}
+l2_misses:                {:08}
+l1_conflict_misses:       {:08}
+l2_conflict_misses:       {:08}
+frame_coloring:           {}
+",
             self.page_hits,
             self.tlb_hits,
             self.tlb_flushes,
             self.attempted_memory_accesses,
             self.correct_memory_accesses,
+            self.page_walks,
+            self.walk_references,
+            self.walk_references_saved,
+            self.write_accesses,
+            self.page_outs,
+            self.swap_ins,
+            self.read_protection_faults,
+            self.write_protection_faults,
+            self.execute_protection_faults,
+            self.forks,
+            self.cow_frames_shared,
+            self.cow_copies,
+            self.cow_frames_shared.saturating_sub(self.cow_copies),
+            self.minor_faults,
+            self.rmap_invalidations,
+            self.segmentation_faults,
+            self.zero_fill_faults,
+            self.stack_growths,
+            self.file_write_backs,
+            self.out_of_range_pages,
+            self.swap_reads,
+            self.swap_writes,
+            self.swap_slots_in_use,
+            self.swap_slots_peak,
+            self.page_faults,
+            self.prefetches,
+            self.prefetch_hits,
+            self.prefetch_wasted,
+            self.fault_around_pages,
+            self.fault_around_io_saved,
+            self.direct_reclaims,
+            self.background_reclaims,
+            self.file_reads,
+            self.soft_faults,
+            self.hard_faults,
+            self.thp_promotions,
+            self.thp_demotions,
+            self.huge_fragmentation,
+            self.pinned_frames,
+            self.oom_kills,
+            self.ksm_merges,
+            self.ksm_unmerges,
+            self.ksm_frames_saved,
+            self.zswap_stores,
+            self.zswap_faults,
+            self.zswap_hits,
+            self.zswap_writebacks,
+            self.zswap_original_bytes,
+            self.zswap_compressed_bytes,
+            self.simulated_time_ns,
+            self.device_requests,
+            self.device_wait_ns,
+            self.fault_service_ns,
+            self.l1_hits,
+            self.l1_misses,
+            self.l2_hits,
+            self.l2_misses,
+            self.l1_conflict_misses,
+            self.l2_conflict_misses,
+            self.frame_coloring,
+        )?;
+        for (level, hits) in self.pwc_hits.iter().enumerate() {
+            let label = format!("pwc_hits_level_{}:", level);
+            writeln!(f, "{:<26}{:08}", label, hits)?;
+        }
+        for (size, hits) in &self.tlb_hits_by_size {
+            let label = format!("tlb_hits_{}x:", size);
+            writeln!(f, "{:<26}{:08}", label, hits)?;
+        }
+        for (size, reach) in &self.tlb_reach_by_size {
+            let label = format!("tlb_reach_{}x:", size);
+            writeln!(f, "{:<26}{:08}", label, reach)?;
+        }
+        write!(
+            f,
+            "
+
+tlb hit ratio:            {:.06}
+page hit ratio:           {:.06}
+l1 hit ratio:             {:.06}
+l2 hit ratio:             {:.06}
+walk savings ratio:       {:.06}
+swap utilization:         {:.06}
+swap fragmentation:       {:.06}
+prefetcher:               {}
+prefetch accuracy:        {:.06}
+prefetch coverage:        {:.06}
+zswap compression ratio:  {:.06}
+zswap hit rate:           {:.06}
+effective access time:    {:.06}
+fault service time:       {:.06}
+               ",
             self.tlb_hits as f32 / self.attempted_memory_accesses as f32,
             self.page_hits as f32 / self.attempted_memory_accesses as f32,
+            self.l1_hits as f32 / (self.l1_hits + self.l1_misses) as f32,
+            self.l2_hits as f32 / (self.l2_hits + self.l2_misses) as f32,
+            self.walk_references_saved as f32
+                / (self.walk_references + self.walk_references_saved) as f32,
+            self.swap_slots_in_use as f32 / self.swap_capacity as f32,
+            self.swap_fragmentation(),
+            self.prefetcher,
+            self.prefetch_hits as f32 / self.prefetches as f32,
+            self.prefetch_hits as f32 / (self.prefetch_hits + self.page_faults) as f32,
+            self.zswap_original_bytes as f32 / self.zswap_compressed_bytes as f32,
+            self.zswap_hits as f32 / (self.zswap_hits + self.swap_reads) as f32,
+            self.effective_access_time(),
+            self.fault_service_ns as f64 / self.page_faults as f64,
         )
     }
 }
@@ -1,20 +1,60 @@
-use crate::address::VirtualAddress;
-use crate::storage::Storage;
+use crate::address::{AccessKind, MemoryAccess, VirtualAddress};
+use crate::cache::DataCache;
+use crate::config::Config;
+use crate::device::Device;
+use crate::prefetch::{Markov, Prefetcher, ReadAhead, Stride};
+use crate::storage::{BackingStore, FileStore, OutOfRange};
+use crate::swap::SwapSpace;
 use crate::tracker::Tracker;
+use crate::vma::{AreaKind, AreaMap, VirtualMemoryArea};
+use crate::walk::PageWalker;
+use crate::MASK_PAGE;
 use linked_hash_map::LinkedHashMap;
-use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::fmt::Debug;
-use std::ops::{Index, IndexMut};
+use std::hash::{Hash, Hasher};
+use std::ops::{Index, IndexMut, Range};
+use std::str::FromStr;
 
 /// Type Alias: A rebranding of the `Result` enum from the standard library which focuses on errors
 /// that may result from improper use of this module.
 type Result<T> = std::result::Result<T, Error>;
 
+/// Type Alias: The identifier of a simulated process (address space).
+pub type Pid = usize;
+
 // The `Error` enum here is merely a formal declaration and generalization of the error kinds that
 // may occur from improper use of other structures later in the module.
 #[derive(Debug)]
 pub enum Error {
     IOError(std::io::Error),
+    ProtectionFault {
+        page_number: usize,
+        kind: AccessKind,
+    },
+    SegmentationFault {
+        page_number: usize,
+    },
+    UnknownProcess(Pid),
+    UnknownSegment(usize),
+    UnknownFile(usize),
+    PageOutOfRange {
+        page_number: usize,
+        store_size: u64,
+    },
+    SwapExhausted,
+    InvalidPageSize {
+        start: usize,
+        page_size: usize,
+    },
+    PinLimitExceeded {
+        limit: usize,
+    },
+    AllFramesPinned,
+    OutOfMemory {
+        pid: Pid,
+    },
 }
 impl From<std::io::Error> for Error {
     fn from(value: std::io::Error) -> Self {
@@ -22,6 +62,15 @@ impl From<std::io::Error> for Error {
     }
 }
 
+/// The `Event` enum records notable occurrences within the virtual memory system which are not
+/// errors yet may be of interest to the caller, such as a process killed by the OOM killer along
+/// with its resident set size at the time. Events accumulate in `VirtualMemory::events` until
+/// drained by the caller.
+#[derive(Debug, PartialEq)]
+pub enum Event {
+    OomKill { pid: Pid, resident_pages: usize },
+}
+
 /// The `AccessResult` encodes the result of an attempted memory access for later use in tracking
 /// the accuracy across the simulation. Properties include the virtual address provided to an
 /// operation, the corresponding physical address, and the value read from that address.
@@ -38,10 +87,18 @@ impl PartialEq for AccessResult {
     }
 }
 
-/// The `TLB` struct is a virtualization of the translation look aside buffer found in CPUs.
+/// The `TLB` struct is a virtualization of the translation look aside buffer found in CPUs. Each
+/// element is tagged with the identifier of the owning process, allowing translations from
+/// several address spaces to coexist without flushing the buffer on a context switch.
+///
+/// Elements may translate pages of differing sizes. Each is keyed by the first logical page of
+/// the (possibly huge) page it covers and holds the frame of that first page along with the page
+/// size in base pages, so a single element translates every base page within a huge page.
+#[allow(clippy::upper_case_acronyms)]
 struct TLB {
     table_size: usize,
-    map: LinkedHashMap<usize, usize>,
+    map: LinkedHashMap<(Pid, usize), (usize, usize)>,
+    sizes: BTreeSet<usize>,
 }
 
 impl TLB {
@@ -50,23 +107,34 @@ impl TLB {
     /// # Arguments
     ///
     /// * `table_size` - an unsigned integer value representing the maximum number of elements in
-    /// the buffer.
+    ///   the buffer.
     fn build(table_size: usize) -> Self {
         Self {
             table_size,
             map: LinkedHashMap::with_capacity(table_size),
+            sizes: BTreeSet::from([1]),
         }
     }
 
-    /// Search the TLB for the requested page and return the result as an `Option`. A `None` value
-    /// implies a TLB fault (cache miss) has occurred.
+    /// Search the TLB for the element covering the requested page and return the frame holding
+    /// the page along with the page size of the element. A `None` value implies a TLB fault
+    /// (cache miss) has occurred.
     ///
     /// # Arguments
     ///
-    /// * `page_number` - The page ID.
+    /// * `key` - The process ID and page ID.
     ///
-    fn find(&self, page_number: usize) -> Option<&usize> {
-        self.map.get(&page_number)
+    fn find(&self, key: (Pid, usize)) -> Option<(usize, usize)> {
+        let (pid, page_number) = key;
+        self.sizes.iter().find_map(|size| {
+            let first_page = page_number & !(size - 1);
+            match self.map.get(&(pid, first_page)) {
+                Some((frame, entry_size)) if entry_size == size => {
+                    Some((frame + page_number - first_page, *size))
+                }
+                _ => None,
+            }
+        })
     }
 
     /// Provided a key (logical page number) and value (physical frame number), cache the mapping
@@ -82,25 +150,108 @@ impl TLB {
     ///
     /// # Arguments
     ///
-    /// * `key` - logical page number
-    /// * `value` - physical frame number
+    /// * `key` - process ID and first logical page number of the page
+    /// * `value` - physical frame number of the first page
+    /// * `size` - page size in base pages
     ///
-    fn cache_element(&mut self, key: usize, value: usize) {
+    fn cache_element(&mut self, key: (Pid, usize), value: usize, size: usize) {
         if self.map.remove(&key).is_none() && self.map.len() == self.table_size {
             self.map.pop_front();
         }
-        self.map.insert(key, value);
+        self.sizes.insert(size);
+        self.map.insert(key, (value, size));
     }
 
-    /// Provided a logical page number (key), ensure the mapping associated with it no longer
+    /// Provided a process ID and logical page number (key), ensure the mapping associated with it no longer
     /// exists in the buffer. A cache flush is required when a multi-level cache is used and
     /// one or more levels have fallen out of alignment with the rest.
     ///
     /// # Arguments
     ///
-    /// * `key` - logical page number
-    fn flush_element(&mut self, key: usize) -> bool {
-        self.map.remove(&key).is_some()
+    /// * `key` - process ID and logical page number
+    fn flush_element(&mut self, key: (Pid, usize)) -> bool {
+        let (pid, page_number) = key;
+        let covering = self.sizes.iter().map(|size| page_number & !(size - 1)).find(|first| {
+            matches!(self.map.get(&(pid, *first)), Some((_, size)) if first + size > page_number)
+        });
+        covering.is_some_and(|first_page| self.map.remove(&(pid, first_page)).is_some())
+    }
+
+    /// Copy the reach of the TLB, being the bytes of memory translated by its elements, into the
+    /// provided tracker for every page size seen.
+    ///
+    /// # Arguments
+    ///
+    /// * `tracker` - the tracker receiving the statistics.
+    /// * `frame_size` - size of a base page in bytes.
+    fn record(&self, tracker: &mut Tracker, frame_size: usize) {
+        for size in &self.sizes {
+            let elements = self.map.values().filter(|(_, other)| other == size).count();
+            tracker
+                .tlb_reach_by_size
+                .insert(*size, elements * size * frame_size);
+            tracker.tlb_hits_by_size.entry(*size).or_default();
+        }
+    }
+}
+
+/// The `Permissions` struct holds the protection bits of a page. An access whose kind is not
+/// permitted results in a protection fault rather than a read or write of the underlying frame.
+/// Pages take the permissions of their virtual memory area unless otherwise specified with
+/// `VirtualMemory::protect`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Permissions {
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+}
+
+impl Permissions {
+    /// Permissions for a page which may be read but neither written nor executed.
+    pub const READ_ONLY: Self = Self {
+        read: true,
+        write: false,
+        execute: false,
+    };
+
+    /// Permissions for a page which may be read and written but not executed.
+    pub const READ_WRITE: Self = Self {
+        read: true,
+        write: true,
+        execute: false,
+    };
+
+    /// Permissions for a page which may be read and executed but not written (e.g. program text).
+    pub const READ_EXECUTE: Self = Self {
+        read: true,
+        write: false,
+        execute: true,
+    };
+
+    /// Permissions for a page which allows every kind of access.
+    pub const ALL: Self = Self {
+        read: true,
+        write: true,
+        execute: true,
+    };
+
+    /// Determine whether the provided kind of access is permitted.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - the kind of memory access attempted.
+    pub fn allows(&self, kind: AccessKind) -> bool {
+        match kind {
+            AccessKind::Read => self.read,
+            AccessKind::Write(_) => self.write,
+            AccessKind::Execute => self.execute,
+        }
+    }
+}
+
+impl Default for Permissions {
+    fn default() -> Self {
+        Self::ALL
     }
 }
 
@@ -109,10 +260,35 @@ impl TLB {
 /// state. Invalid references (simulated dangling pointers) occur when the data referenced
 /// originally has been paged out. Recall that a finite number of frames serve a seamingly infinite
 /// number of logical pages.
-#[derive(Debug, PartialEq)]
+///
+/// Pages modified since they were last paged in are marked `dirty` and must be written to swap
+/// space before their frame is reused. Once that occurs the page holds a `swap_slot`, signaling
+/// that swap space rather than the backing store holds its most recent contents. The slot is kept
+/// until the page is freed. Pages marked
+/// `cow` (copy-on-write) may share their frame with pages of other processes and must receive a
+/// private copy of it before being written. Pages marked `prefetched` were loaded speculatively
+/// and have yet to be referenced, as were pages marked `clustered`, which were read alongside a
+/// neighboring page during fault-around. Pages belonging to a `huge` page record its size in base
+/// pages, with every page of it held by one of a contiguous run of frames. Pages marked
+/// `untouched` were paged in as part of a huge page and have yet to be referenced, so their frames
+/// are the internal fragmentation cost of the huge page. Pages marked `pinned` hold their frame
+/// until unpinned (see `VirtualMemory::pin`). Pages marked `merged` share their frame with pages of
+/// identical contents through same-page merging and are copy-on-write (see
+/// `VirtualMemory::merge_same_pages`).
+#[derive(Debug, Default, Clone, PartialEq)]
 struct Page {
     frame_index: usize,
     valid: bool,
+    dirty: bool,
+    swap_slot: Option<usize>,
+    cow: bool,
+    prefetched: bool,
+    clustered: bool,
+    huge: Option<usize>,
+    untouched: bool,
+    pinned: bool,
+    merged: bool,
+    permissions: Permissions,
 }
 
 /// The `PageTable` struct is little more than a wrapper around the standard Rust library `HashMap`
@@ -165,16 +341,40 @@ impl PageTable {
     fn insert(&mut self, id: usize, page: Page) {
         self.0.insert(id, page);
     }
+
+    /// Remove an element from the page table, returning it if present. Pages are only removed
+    /// when the virtual memory area containing them is unmapped.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - logical page number.
+    ///
+    fn remove(&mut self, id: usize) -> Option<Page> {
+        self.0.remove(&id)
+    }
+
+    /// Return an iterator over every (logical page number, page) pair in the table where the
+    /// pages are mutable.
+    fn iter_mut(&mut self) -> impl Iterator<Item = (&usize, &mut Page)> {
+        self.0.iter_mut()
+    }
+
+    /// Return an iterator over every (logical page number, page) pair in the table.
+    fn iter(&self) -> impl Iterator<Item = (&usize, &Page)> {
+        self.0.iter()
+    }
 }
 
 /// The `Frame` struct contains a buffer with a length defined as the frame size in bytes. It is
 /// intended to be the simplest element of the `FrameTable` and represents memory that can be
-/// swapped in and out via demand paging. An associated `page_id` element is kept simply for record
-/// keeping and to minimize the effort required to invalidate the corresponding entry in the page
-/// table when a frame is victimized (paged-out).
+/// swapped in and out via demand paging. The `mappings` element is a reverse mapping listing
+/// every (process, logical page number) pair which maps the frame. It exists to minimize the effort
+/// required to invalidate the corresponding entries in each page table when a frame is victimized
+/// (paged-out), since a frame may be shared after a `fork` or through a shared memory segment.
 struct Frame {
     buffer: Vec<u8>,
-    associated_page_id: usize,
+    mappings: Vec<(Pid, usize)>,
+    cached: Option<(Pid, usize)>,
 }
 
 impl Frame {
@@ -187,10 +387,16 @@ impl Frame {
     ///
     fn new(frame_size: u64) -> Self {
         Self {
-            buffer: vec![0 as u8; frame_size as usize],
-            associated_page_id: usize::MAX,
+            buffer: vec![0_u8; frame_size as usize],
+            mappings: Vec::new(),
+            cached: None,
         }
     }
+
+    /// Return the number of page table entries which map the frame.
+    fn ref_count(&self) -> usize {
+        self.mappings.len()
+    }
 }
 
 impl Index<usize> for Frame {
@@ -207,6 +413,33 @@ impl IndexMut<usize> for Frame {
     }
 }
 
+/// The `FrameColoring` enum is the policy applied when choosing a frame from the free pool. The
+/// color of a frame is its frame number modulo the number of colors, which is the number of frames
+/// spanned by a single way of the physically indexed cache, so that frames of distinct colors
+/// never compete for the same cache sets. Frames may be allocated without regard to their color
+/// (`None`), with the color matching the low bits of the virtual page number (`PageColoring`), or
+/// with each allocation taking the color after that of the last (`BinHopping`). A colored
+/// allocation falls back to the next free frame of any color should none of its color be free.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameColoring {
+    None,
+    PageColoring,
+    BinHopping,
+}
+
+impl FromStr for FrameColoring {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "page-coloring" => Ok(Self::PageColoring),
+            "bin-hopping" => Ok(Self::BinHopping),
+            _ => Err(format!("unknown frame coloring policy: '{}'", s)),
+        }
+    }
+}
+
 /// The `FrameTable` struct simulates the behavior of physical memory frames relative to the
 /// operating system. While the `PageTable` may possess a seemingly infinite number of pages, the
 /// `FrameTable` is limited to a finite amount to mimic the constraints physical memory. Here, the
@@ -216,11 +449,23 @@ impl IndexMut<usize> for Frame {
 /// Instances of the `FrameTable` struct are predominantly buffers containing references to other
 /// buffers (frames). Additional elements within the struct exist merely for housekeeping or for
 /// the sake of the victimization algorithm responsible for ensuring continued allocation
-/// operations at the expense of infrequently used chunks of memory.
+/// operations at the expense of infrequently used chunks of memory. Every frame is either in the
+/// pool of free frames, on the list of modified frames awaiting write-back, or in use, in which
+/// case it is queued for victimization unless pinned.
+///
+/// With page buffering, a reclaimed frame keeps its contents along with the identity of the page
+/// it held (`Frame::cached`) until the frame is allocated anew, so that a fault on the page may
+/// rescue the frame rather than reading the page again.
 struct FrameTable {
     frame_size: u64,
     entries: Vec<Frame>,
+    free: VecDeque<usize>,
+    modified: VecDeque<usize>,
     victimizer: LinkedHashMap<usize, usize>,
+    pinned: HashMap<usize, usize>,
+    coloring: FrameColoring,
+    colors: usize,
+    next_color: usize,
 }
 
 impl FrameTable {
@@ -232,31 +477,118 @@ impl FrameTable {
     /// * `table_size` - size of the frame table.
     /// * `frame_size` - size any frame within the table.
     fn build(table_size: usize, frame_size: u64) -> Self {
-        let mut entries: Vec<Frame> = Vec::with_capacity(table_size);
-        let mut victimizer = LinkedHashMap::new();
-        (0..table_size).for_each(|index| {
-            entries.push(Frame::new(frame_size));
-            victimizer.insert(index, index);
-        });
-
         Self {
             frame_size,
-            entries,
-            victimizer,
+            entries: (0..table_size).map(|_| Frame::new(frame_size)).collect(),
+            free: (0..table_size).collect(),
+            modified: VecDeque::new(),
+            victimizer: LinkedHashMap::new(),
+            pinned: HashMap::new(),
+            coloring: FrameColoring::None,
+            colors: 1,
+            next_color: 0,
         }
     }
 
-    /// Instruct the frame table to allocate a free frame regardless of whether one is available.
-    /// Should it be the case that no free frames are available, the victimization algorithm is
-    /// used to select an allocated frame to be replaced with new data. In other words, the victim
-    /// frame is paged-out. Often in consumer computer systems, the victim frame's data is moved to
-    /// swap space assuming the system is configured to use it. Although significantly slower,
-    /// there are still merits to using a system-managed raw partition relative to the virtual
-    /// memory implementation.
-    fn allocate(&mut self) -> usize {
-        let value = self.victimizer.pop_front().expect("should have victims").0;
+    /// Allocate a frame of the free pool for the provided page, placing it at the back of the
+    /// victimization queue. The earliest freed frame of the color chosen by the coloring policy is
+    /// taken (see `FrameColoring`), or the earliest freed frame of any color should there be none.
+    /// Should the pool be empty, `None` is returned and an allocated frame must first be reclaimed
+    /// (see `victim` and `release`).
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID of the page the frame is allocated for.
+    fn allocate(&mut self, page_number: usize) -> Option<usize> {
+        let color = match self.coloring {
+            FrameColoring::None => None,
+            FrameColoring::PageColoring => Some(page_number % self.colors),
+            FrameColoring::BinHopping => {
+                let color = self.next_color;
+                self.next_color = (color + 1) % self.colors;
+                Some(color)
+            }
+        };
+        let position = color
+            .and_then(|color| {
+                self.free
+                    .iter()
+                    .position(|index| index % self.colors == color)
+            })
+            .unwrap_or(0);
+        let value = self.free.remove(position)?;
+        self.entries[value].cached = None;
         self.victimizer.insert(value, value);
-        value
+        Some(value)
+    }
+
+    /// Return the frames of the victimization queue in the order the victimization algorithm
+    /// selects them. The caller removes the chosen victim from the queue and pages-out the frame
+    /// before it is returned to the free pool. Often in consumer computer systems, the victim
+    /// frame's data is moved to swap space assuming the system is configured to use it.
+    fn victims(&self) -> impl Iterator<Item = usize> + '_ {
+        self.victimizer.keys().copied()
+    }
+
+    /// Take the run of `size` frames beginning at `first` from the free pool, placing each at the
+    /// back of the victimization queue. Every frame of the run must be free.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - index of the first frame of the run
+    /// * `size` - number of frames in the run
+    fn allocate_block(&mut self, first: usize, size: usize) {
+        let block = first..first + size;
+        self.free.retain(|index| !block.contains(index));
+        for index in block {
+            self.entries[index].cached = None;
+            self.victimizer.insert(index, index);
+        }
+    }
+
+    /// Return a frame which is no longer in use to the free pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - index of the target frame
+    fn release(&mut self, index: usize) {
+        self.free.push_back(index);
+    }
+
+    /// Should a frame of the free pool or modified list still hold the provided page, remove it
+    /// from whichever list holds it and place it back in use, returning whether it was rescued.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - index of the frame last holding the page
+    /// * `page` - the process and logical page number of the page
+    fn rescue(&mut self, index: usize, page: (Pid, usize)) -> bool {
+        if self.entries[index].cached != Some(page) {
+            return false;
+        }
+        self.entries[index].cached = None;
+        self.free.retain(|other| *other != index);
+        self.modified.retain(|other| *other != index);
+        self.victimizer.insert(index, index);
+        true
+    }
+
+    /// Discard the identity of a page held by a frame of the free pool or modified list, such as
+    /// when the page is unmapped. A modified frame is moved to the free pool without being written.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - index of the frame last holding the page
+    /// * `page` - the process and logical page number of the page
+    fn forget(&mut self, index: usize, page: (Pid, usize)) {
+        if self.entries[index].cached != Some(page) {
+            return;
+        }
+        self.entries[index].cached = None;
+        if let Some(position) = self.modified.iter().position(|other| *other == index) {
+            self.modified.remove(position);
+            self.free.push_back(index);
+        }
     }
 
     /// Reference a frame within the table to reset its' position within the victimization queue.
@@ -265,20 +597,203 @@ impl FrameTable {
     ///
     /// * `index` - index of the target frame
     fn reference(&mut self, index: usize) {
+        if self.pinned.contains_key(&index) {
+            return;
+        }
         self.victimizer.remove(&index).unwrap();
         self.victimizer.insert(index, index);
     }
+
+    /// Pin a frame in use, removing it from the victimization queue so that it is never selected
+    /// as a victim. A frame pinned several times (once for each page pinned to it) remains pinned
+    /// until unpinned as many times.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - index of the target frame
+    fn pin(&mut self, index: usize) {
+        self.victimizer.remove(&index);
+        *self.pinned.entry(index).or_default() += 1;
+    }
+
+    /// Release one pin of a frame, returning it to the back of the victimization queue once no
+    /// pins remain.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - index of the target frame
+    fn unpin(&mut self, index: usize) {
+        match self.pinned.get_mut(&index) {
+            Some(count) if *count > 1 => *count -= 1,
+            Some(_) => {
+                self.pinned.remove(&index);
+                self.victimizer.insert(index, index);
+            }
+            None => (),
+        }
+    }
+
+    /// Provided frames in use, find those whose contents are identical to those of an earlier frame
+    /// provided, returning each such frame paired with the earliest frame it duplicates. As with
+    /// KSM on Linux, frames are hashed to find candidates which are then compared in full.
+    ///
+    /// # Arguments
+    ///
+    /// * `indices` - indices of the frames to be scanned
+    fn identical(&self, indices: impl IntoIterator<Item = usize>) -> Vec<(usize, usize)> {
+        let mut stable: HashMap<u64, Vec<usize>> = HashMap::new();
+        let mut duplicates = Vec::new();
+        for index in indices {
+            let buffer = &self.entries[index].buffer;
+            let mut hasher = DefaultHasher::new();
+            buffer.hash(&mut hasher);
+            let candidates = stable.entry(hasher.finish()).or_default();
+            match candidates
+                .iter()
+                .find(|candidate| self.entries[**candidate].buffer == *buffer)
+            {
+                Some(original) => duplicates.push((index, *original)),
+                None => candidates.push(index),
+            }
+        }
+        duplicates
+    }
+
+    /// Move the provided frames to the front of the victimization queue, in the order given, so
+    /// that they are the next to be victimized unless referenced beforehand.
+    ///
+    /// # Arguments
+    ///
+    /// * `indices` - indices of the target frames
+    fn demote(&mut self, indices: &[usize]) {
+        let mut victimizer: LinkedHashMap<usize, usize> =
+            indices.iter().map(|index| (*index, *index)).collect();
+        while let Some((index, _)) = self.victimizer.pop_front() {
+            victimizer.entry(index).or_insert(index);
+        }
+        self.victimizer = victimizer;
+    }
+}
+
+/// The `SharedPage` struct tracks where the contents of a single page of a shared memory segment
+/// reside. While resident, every process attached to the segment maps the same frame.
+#[derive(Debug, Default, Clone)]
+struct SharedPage {
+    frame_index: Option<usize>,
+    swap_slot: Option<usize>,
+}
+
+/// The `SharedSegment` struct represents a region of memory which may be attached to the address
+/// spaces of several processes at once, similar to System V shared memory. Segments are
+/// zero-filled when first referenced.
+struct SharedSegment {
+    pages: Vec<SharedPage>,
+}
+
+impl SharedSegment {
+    /// Create a new `SharedSegment` spanning the provided number of pages.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - number of pages in the segment.
+    fn build(length: usize) -> Self {
+        Self {
+            pages: vec![SharedPage::default(); length],
+        }
+    }
+}
+
+/// The `SharedObject` enum identifies what backs a page mapped by several address spaces at once:
+/// either a shared memory segment or an open file mapped with shared semantics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SharedObject {
+    Segment(usize),
+    File(usize),
+}
+
+/// The `Process` struct represents a single simulated address space. Each process owns a page
+/// table and the virtual memory areas describing which pages may be referenced, while the TLB,
+/// frame table, and storage are shared among all of them as they would be on a physical machine.
+struct Process {
+    pages: PageTable,
+    areas: AreaMap,
+}
+
+impl Process {
+    /// Create a new `Process` instance with an empty page table and the provided areas.
+    ///
+    /// # Arguments
+    ///
+    /// * `areas` - the virtual memory areas of the address space.
+    fn build(areas: AreaMap) -> Self {
+        Self {
+            pages: PageTable::build(),
+            areas,
+        }
+    }
+
+    /// Return the resident set size of the process, being the number of its pages which are
+    /// resident.
+    fn resident_set_size(&self) -> usize {
+        self.pages.iter().filter(|(_, page)| page.valid).count()
+    }
+}
+
+/// The `Watermarks` struct holds the thresholds, in free frames, which govern page reclaim. An
+/// allocation which would leave no more than `min` frames free reclaims frames itself (direct
+/// reclaim). Separately, the background reclaimer wakes periodically and, should fewer than `low`
+/// frames be free, reclaims frames until `high` are free, similar to `kswapd` on Linux.
+struct Watermarks {
+    min: usize,
+    low: usize,
+    high: usize,
+}
+
+/// The `Latencies` struct holds the time, in nanoseconds, charged to the virtual clock for each
+/// step of an access: the TLB lookup, each memory reference made by the page walker, the access to
+/// physical memory itself, and the service of a page fault.
+struct Latencies {
+    tlb: usize,
+    walk_level: usize,
+    memory: usize,
+    fault: usize,
 }
 
 /// The `VirtualMemory` struct is the culmination of all other structures and procedures in this
 /// module. The core purpose of each instance is to simulate the behavior of a virtual memory
 /// system with only a modest amount of configuration. Ideally, it should behave as a standard
 /// testing system for different algorithms, albeit with minor reconfiguration.
+///
+/// Accesses are made on behalf of the current process, which is the initial process (PID zero)
+/// unless changed with `switch_process`. Additional processes are created with `fork`. The
+/// initial process begins with a single area spanning its entire address space and backed by the
+/// backing store, which may be reshaped with `map_area`. Further files may be opened with
+/// `open_file` and mapped alongside it.
 pub struct VirtualMemory {
     tlb: TLB,
-    pages: PageTable,
+    walker: PageWalker,
+    processes: HashMap<Pid, Process>,
+    segments: HashMap<usize, SharedSegment>,
+    file_pages: HashMap<(usize, usize), usize>,
+    current: Pid,
+    next_pid: Pid,
     frames: FrameTable,
-    storage: Storage,
+    files: Vec<Box<dyn BackingStore>>,
+    out_of_range: OutOfRange,
+    prefetcher: Option<Box<dyn Prefetcher>>,
+    fault_around: usize,
+    watermarks: Watermarks,
+    reclaim_interval: usize,
+    size_modified_list: usize,
+    size_thp: usize,
+    thp_scan_interval: usize,
+    max_pinned_frames: usize,
+    ksm_scan_interval: usize,
+    latencies: Latencies,
+    device: Device,
+    cache: DataCache,
+    swap: SwapSpace,
+    pub events: Vec<Event>,
     pub tracker: Tracker,
 }
 
@@ -287,22 +802,88 @@ impl VirtualMemory {
     ///
     /// # Arguments
     ///
-    /// * `tlb_size` - TLB cache size.
-    /// * `frame_table_size` - number of frame table entries.
-    /// * `frame_size` - size of any frame in bytes.
+    /// * `config` - An instance of the configuration struct which contains the cache, table, and
+    ///   frame sizes along with the swap filename.
+    /// * `store` - the backing store read by the initial process, which may be of any kind (see
+    ///   `BackingStore`).
     ///
-    pub fn build(
-        tlb_size: usize,
-        frame_table_size: usize,
-        frame_size: u64,
-        file_storage: &str,
-    ) -> Self {
+    pub fn build<S: BackingStore + 'static>(config: &Config, store: S) -> Self {
+        let walk_levels = config.page_walk_levels as usize;
+        let mut tracker = Tracker::new();
+        tracker.pwc_hits = vec![0; walk_levels - 1];
+        tracker.prefetcher = config.prefetcher.clone();
+        tracker.frame_coloring = config.frame_coloring.clone();
+        let mut areas = AreaMap::new();
+        areas.insert(VirtualMemoryArea {
+            page_size: config.size_huge_page as usize,
+            ..VirtualMemoryArea::new(
+                0,
+                1 << MASK_PAGE.count_ones(),
+                AreaKind::File {
+                    file: 0,
+                    page_offset: 0,
+                    shared: false,
+                },
+                Permissions::ALL,
+            )
+        });
         Self {
-            tlb: TLB::build(tlb_size),
-            pages: PageTable::build(),
-            frames: FrameTable::build(frame_table_size, frame_size),
-            storage: Storage::build(file_storage),
-            tracker: Tracker::new(),
+            tlb: TLB::build(config.size_tlb as usize),
+            walker: PageWalker::build(walk_levels, config.size_pwc as usize),
+            processes: HashMap::from([(0, Process::build(areas))]),
+            segments: HashMap::new(),
+            file_pages: HashMap::new(),
+            current: 0,
+            next_pid: 1,
+            frames: FrameTable {
+                coloring: config
+                    .frame_coloring
+                    .parse()
+                    .unwrap_or_else(|err| panic!("{}", err)),
+                colors: (config.size_l2 / config.l2_ways / config.size_frame).max(1) as usize,
+                ..FrameTable::build(config.size_table as usize, config.size_frame as u64)
+            },
+            files: vec![Box::new(store)],
+            out_of_range: config
+                .out_of_range
+                .parse()
+                .unwrap_or_else(|err| panic!("{}", err)),
+            prefetcher: match config.prefetcher.as_str() {
+                "readahead" => Some(Box::new(ReadAhead::build(config.prefetch_window as usize))),
+                "stride" => Some(Box::new(Stride::build(config.prefetch_window as usize))),
+                "markov" => Some(Box::new(Markov::build(
+                    config.prefetch_window as usize,
+                    config.size_markov_table as usize,
+                ))),
+                _ => None,
+            },
+            fault_around: config.fault_around as usize,
+            watermarks: Watermarks {
+                min: config.watermark_min as usize,
+                low: config.watermark_low as usize,
+                high: config.watermark_high as usize,
+            },
+            reclaim_interval: config.reclaim_interval as usize,
+            size_modified_list: config.size_modified_list as usize,
+            size_thp: config.size_thp as usize,
+            thp_scan_interval: config.thp_scan_interval as usize,
+            max_pinned_frames: config.max_pinned_frames as usize,
+            ksm_scan_interval: config.ksm_scan_interval as usize,
+            latencies: Latencies {
+                tlb: config.latency_tlb as usize,
+                walk_level: config.latency_walk_level as usize,
+                memory: config.latency_memory as usize,
+                fault: config.latency_fault as usize,
+            },
+            device: Device::build(config),
+            cache: DataCache::build(config),
+            swap: SwapSpace::build(
+                FileStore::build_swap(&config.file_swap),
+                config.size_swap as usize,
+                config.size_zswap as usize,
+            ),
+            events: Vec::new(),
+            tracker,
         }
     }
 
@@ -323,95 +904,2282 @@ impl VirtualMemory {
     /// # Errors
     ///
     /// An error will occur if an invalid frame retrieval request is executed (e.g. out-of-bounds
-    /// memory access).
+    /// memory access), the page lies outside every virtual memory area of the process, or the
+    /// page permissions forbid the access (see `protect`).
     pub fn access(&mut self, virtual_address: VirtualAddress) -> Result<AccessResult> {
-        self.tracker.attempted_memory_accesses += 1;
-        let page_number = virtual_address.number_page as usize;
-        let offset = virtual_address.number_offset as usize;
-        let frame_index = match self.tlb.find(page_number) {
-            Some(x) => {
-                self.tracker.tlb_hits += 1;
-                *x
-            }
-            _ => match self.pages.find(page_number) {
-                Some(page) if page.valid => {
-                    self.tracker.page_hits += 1;
-                    self.tlb.cache_element(page_number, page.frame_index);
-                    page.frame_index
-                }
-                _ => {
-                    let fi = self.retrieve_frame(virtual_address.number_page as usize)?;
-                    self.tlb.cache_element(page_number, fi);
-                    fi
-                }
-            },
-        };
+        self.perform(MemoryAccess {
+            virtual_address,
+            kind: AccessKind::Read,
+        })
+    }
 
-        self.frames.reference(frame_index);
-        Ok(AccessResult {
+    /// Using the simulated virtual memory system, store the provided value at the logical
+    /// address given. The page written to is marked dirty so that its contents are preserved in
+    /// swap space should it later be paged out. The value written is returned to the caller in
+    /// the same manner as a read performed by `access`.
+    ///
+    /// # Arguments
+    ///
+    /// * `virtual_address` - the process-facing logical address used for indirect data access
+    /// * `value` - the byte to be stored
+    ///
+    /// # Errors
+    ///
+    /// See `access`.
+    pub fn write(&mut self, virtual_address: VirtualAddress, value: i8) -> Result<AccessResult> {
+        self.perform(MemoryAccess {
             virtual_address,
-            physical_address: ((frame_index * self.frames.frame_size as usize) + offset) as u32,
-            value: self.frames.entries[frame_index][offset] as i8,
+            kind: AccessKind::Write(value),
         })
     }
 
-    /// Provided a logical page number, allocate a free frame and read the data referenced by the
-    /// page into the frame buffer to maintain the illusion of unmanaged memory access from the
-    /// perspective of the process.
+    /// Carry out a memory access of any kind (see `access` and `write`). This is the entry point
+    /// used when replaying an address trace.
+    ///
+    /// Rather than taking any real time, each access advances a virtual clock
+    /// (`Tracker::simulated_time_ns`) by the configured latencies: one TLB lookup, one memory
+    /// reference per page table level walked, the fault handling overhead of each page fault, and
+    /// one access to physical memory should the access succeed. Should the access read or write a
+    /// store, it also waits for the device to complete those requests (see `Device`), which
+    /// together with the fault handling overhead makes up the fault service time.
     ///
     /// # Arguments
     ///
-    /// * `page_number` - logical page number/ID.
+    /// * `access` - the virtual address and kind of access requested.
     ///
     /// # Errors
     ///
-    /// An error will occur if the storage read operation is passed invalid arguments (e.g. reading
-    /// past the end of the simulated backing store). The error value is returned to the caller in
-    /// the form of the `Error` enum variant.
-    fn retrieve_frame(&mut self, page_number: usize) -> Result<usize> {
-        let frame_index = self.frames.allocate();
-        let frame = &mut self.frames.entries[frame_index];
-        if let Some(page) = self.pages.find_mut(frame.associated_page_id) {
-            page.valid = false;
-            if self.tlb.flush_element(frame.associated_page_id) {
-                self.tracker.tlb_flushes += 1;
-            }
+    /// See `access`.
+    pub fn perform(&mut self, access: MemoryAccess) -> Result<AccessResult> {
+        let start = self.tracker.simulated_time_ns;
+        let walk_references = self.tracker.walk_references;
+        let page_faults = self.tracker.page_faults;
+        let (requests, pages) = self.transfers();
+        let result = self.perform_untimed(access);
+
+        let Latencies {
+            tlb,
+            walk_level,
+            memory,
+            fault,
+        } = self.latencies;
+        let translated =
+            start + tlb + (self.tracker.walk_references - walk_references) * walk_level;
+        let mut serviced = translated + (self.tracker.page_faults - page_faults) * fault;
+        let transfers = self.transfers();
+        if transfers.0 > requests {
+            let bytes = (transfers.1 - pages) * self.frames.frame_size as usize;
+            serviced = self.device.submit(serviced, transfers.0 - requests, bytes);
         }
-        frame.associated_page_id = page_number;
-        self.storage.read(page_number as u64, &mut frame.buffer)?;
-        self.pages.insert(
-            page_number as usize,
-            Page {
-                frame_index,
-                valid: true,
-            },
-        );
+        self.tracker.fault_service_ns += serviced - translated;
+        self.tracker.simulated_time_ns = serviced + if result.is_ok() { memory } else { 0 };
 
-        Ok(frame_index)
+        let result = result.and_then(|result| self.run_background().map(|_| result));
+        self.swap.record(&mut self.tracker);
+        self.device.record(&mut self.tracker);
+        self.cache.record(&mut self.tracker);
+        self.tlb
+            .record(&mut self.tracker, self.frames.frame_size as usize);
+        result
     }
-}
 
-#[cfg(test)]
-mod tests {
+    /// Return the number of requests made to the device so far, along with the number of pages
+    /// they transferred (see `Device`).
+    fn transfers(&self) -> (usize, usize) {
+        let requests =
+            self.tracker.file_reads + self.tracker.file_write_backs + self.swap.transfers();
+        (requests, requests + self.tracker.fault_around_pages)
+    }
 
-    use super::*;
+    /// Run the periodic background tasks which are due following an access: the background
+    /// reclaimer, the huge page collapse scan, and the same-page merging scan. Their requests to
+    /// the device are submitted without advancing the virtual clock, although later requests may
+    /// queue behind them.
+    ///
+    /// # Errors
+    ///
+    /// See `reclaim_background` and `collapse_huge_pages`.
+    fn run_background(&mut self) -> Result<()> {
+        let (requests, pages) = self.transfers();
+        if self.reclaim_interval > 0
+            && self
+                .tracker
+                .attempted_memory_accesses
+                .is_multiple_of(self.reclaim_interval)
+        {
+            self.reclaim_background()?;
+        }
+        if self.size_thp > 1
+            && self
+                .tracker
+                .attempted_memory_accesses
+                .is_multiple_of(self.thp_scan_interval)
+        {
+            self.collapse_huge_pages()?;
+        }
+        if self.ksm_scan_interval > 0 {
+            if self
+                .tracker
+                .attempted_memory_accesses
+                .is_multiple_of(self.ksm_scan_interval)
+            {
+                self.merge_same_pages();
+            }
+            self.tracker.ksm_frames_saved = self.merged_frames_saved();
+        }
+        let transfers = self.transfers();
+        if transfers.0 > requests {
+            let bytes = (transfers.1 - pages) * self.frames.frame_size as usize;
+            self.device.submit(
+                self.tracker.simulated_time_ns,
+                transfers.0 - requests,
+                bytes,
+            );
+        }
+        Ok(())
+    }
 
-    const SIZE_FRAME: u64 = 256;
-    const SIZE_TABLE: usize = 256;
+    /// Carry out a memory access as described by `perform` without advancing the virtual clock
+    /// or running background tasks.
+    ///
+    /// # Arguments
+    ///
+    /// * `access` - the virtual address and kind of access requested.
+    ///
+    /// # Errors
+    ///
+    /// See `access`.
+    fn perform_untimed(&mut self, access: MemoryAccess) -> Result<AccessResult> {
+        let MemoryAccess {
+            virtual_address,
+            kind,
+        } = access;
+        self.tracker.attempted_memory_accesses += 1;
+        let page_number = virtual_address.number_page as usize;
+        let offset = virtual_address.number_offset as usize;
+        self.check_area(page_number)?;
+        self.check_permissions(page_number, kind)?;
+        let mut frame_index = self.translate(page_number)?;
 
-    #[cfg(test)]
-    mod page_tests {
+        if let AccessKind::Write(value) = kind {
+            frame_index = self.copy_on_write(page_number, frame_index)?;
+            self.tracker.write_accesses += 1;
+            self.frames.entries[frame_index][offset] = value as u8;
+            if let Some(page) = self.pages_mut().find_mut(page_number) {
+                page.dirty = true;
+            }
+        }
+        self.frames.reference(frame_index);
+        let result = AccessResult {
+            virtual_address,
+            physical_address: ((frame_index * self.frames.frame_size as usize) + offset) as u32,
+            value: self.frames.entries[frame_index][offset] as i8,
+        };
+        self.cache.access(result.physical_address as usize);
+        Ok(result)
+    }
 
-        use super::*;
+    /// Change the protection bits of a page owned by the current process, similar to `mprotect`
+    /// on POSIX systems. The page need not be resident. Any cached translation of the page is
+    /// flushed from the TLB so the new permissions take effect immediately. Nothing is done should
+    /// the current process have been killed (see `oom_kill`).
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID.
+    /// * `permissions` - the kinds of access to be permitted on the page.
+    pub fn protect(&mut self, page_number: usize, permissions: Permissions) {
+        let Some(process) = self.processes.get_mut(&self.current) else {
+            return;
+        };
+        let pages = &mut process.pages;
+        match pages.find_mut(page_number) {
+            Some(page) => page.permissions = permissions,
+            None => pages.insert(
+                page_number,
+                Page {
+                    permissions,
+                    ..Default::default()
+                },
+            ),
+        }
+        if self.tlb.flush_element((self.current, page_number)) {
+            self.tracker.tlb_flushes += 1;
+        }
+    }
 
-        #[test]
-        fn new() {
-            let page = Page {
-                frame_index: 0xF,
-                valid: false,
-            };
-            assert_eq!(page.valid, false);
-            assert_eq!(page.frame_index, 0xF);
+    /// Pin a page of the current process in memory, similar to `mlock` on POSIX systems. The page
+    /// is paged in should it not be resident, after which its frame is never victimized until the
+    /// page is unpinned or unmapped. A page belonging to a huge page is first split from it.
+    /// Pinning a page which is already pinned has no effect.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID.
+    ///
+    /// # Errors
+    ///
+    /// An `UnknownProcess` error is returned should the current process have been killed (see
+    /// `oom_kill`). A `SegmentationFault` error is returned if the page lies outside every area,
+    /// while a `PinLimitExceeded` error is returned if pinning the page would exceed the limit on
+    /// pinned frames. Any error encountered while paging in the page is also returned.
+    pub fn pin(&mut self, page_number: usize) -> Result<()> {
+        let process = self
+            .processes
+            .get(&self.current)
+            .ok_or(Error::UnknownProcess(self.current))?;
+        if process.areas.find(page_number).is_none() {
+            return Err(Error::SegmentationFault { page_number });
+        }
+        if self
+            .process()
+            .pages
+            .find(page_number)
+            .is_some_and(|page| page.valid && page.pinned)
+        {
+            return Ok(());
+        }
+        let frame_index = self.translate(page_number)?;
+        if !self.frames.pinned.contains_key(&frame_index)
+            && self.frames.pinned.len() >= self.max_pinned_frames
+        {
+            return Err(Error::PinLimitExceeded {
+                limit: self.max_pinned_frames,
+            });
+        }
+        self.split_huge(self.current, page_number);
+        self.frames.pin(frame_index);
+        self.pages_mut()
+            .find_mut(page_number)
+            .expect("translated page should exist")
+            .pinned = true;
+        self.tracker.pinned_frames = self.frames.pinned.len();
+        Ok(())
+    }
+
+    /// Unpin a page of the current process, similar to `munlock` on POSIX systems, so that its
+    /// frame may once again be victimized. Unpinning a page which is not pinned, or a page of a
+    /// current process which has been killed, has no effect.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID.
+    pub fn unpin(&mut self, page_number: usize) {
+        let Some(page) = self
+            .processes
+            .get_mut(&self.current)
+            .and_then(|process| process.pages.find_mut(page_number))
+        else {
+            return;
+        };
+        if !std::mem::take(&mut page.pinned) {
+            return;
+        }
+        let frame_index = page.frame_index;
+        self.frames.unpin(frame_index);
+        self.tracker.pinned_frames = self.frames.pinned.len();
+    }
+
+    /// Return the identifier of the process on whose behalf accesses are currently made.
+    pub fn current_process(&self) -> Pid {
+        self.current
+    }
+
+    /// Make the provided process the current process so that subsequent accesses are translated
+    /// using its page table. The TLB is tagged with process identifiers and therefore need not be
+    /// flushed.
+    ///
+    /// # Arguments
+    ///
+    /// * `pid` - identifier of the process to switch to.
+    ///
+    /// # Errors
+    ///
+    /// An `UnknownProcess` error is returned if no such process exists.
+    pub fn switch_process(&mut self, pid: Pid) -> Result<()> {
+        if !self.processes.contains_key(&pid) {
+            return Err(Error::UnknownProcess(pid));
+        }
+        self.current = pid;
+        Ok(())
+    }
+
+    /// Create a child process by cloning the page table of the parent, similar to `fork` on POSIX
+    /// systems. Rather than copying resident pages, the child shares the parent's frames. Shared
+    /// pages which permit writes are marked copy-on-write in both processes so that the first
+    /// write by either one receives a private copy of the frame (see `copy_on_write`). The child
+    /// inherits the virtual memory areas of the parent, and pages of shared memory segments remain
+    /// shared by both. The identifier of the child is returned, although the current process
+    /// remains unchanged.
+    ///
+    /// Pages held in swap space are copied into newly allocated swap slots for the child since
+    /// swap slots are owned by a single page.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent` - identifier of the process to fork.
+    ///
+    /// # Errors
+    ///
+    /// An `UnknownProcess` error is returned if the parent does not exist, while an `IOError` may
+    /// result from copying swap space, or a `SwapExhausted` error should no swap slots remain for
+    /// the copies.
+    pub fn fork(&mut self, parent: Pid) -> Result<Pid> {
+        self.write_modified()?;
+        let mut child_slots = self.copy_swap_slots(parent)?;
+        let child = self.next_pid;
+        let Process {
+            pages: parent_pages,
+            areas: parent_areas,
+        } = self
+            .processes
+            .get_mut(&parent)
+            .ok_or(Error::UnknownProcess(parent))?;
+
+        let mut child_process = Process::build(parent_areas.clone());
+        for (page_number, page) in parent_pages.iter_mut() {
+            if page.valid {
+                let frame = &mut self.frames.entries[page.frame_index];
+                frame.mappings.push((child, *page_number));
+                if shared_page(parent_areas, *page_number).is_none() {
+                    page.cow = page.cow || page.permissions.write;
+                    self.tracker.cow_frames_shared += 1;
+                }
+            }
+            let child_page = Page {
+                prefetched: false,
+                clustered: false,
+                untouched: false,
+                pinned: false,
+                merged: false,
+                swap_slot: child_slots.remove(page_number),
+                ..page.clone()
+            };
+            child_process.pages.insert(*page_number, child_page);
+        }
+
+        self.swap.record(&mut self.tracker);
+        self.processes.insert(child, child_process);
+        self.next_pid += 1;
+        self.tracker.forks += 1;
+        Ok(child)
+    }
+
+    /// Provided a process identifier, copy every page the process holds in swap space into a newly
+    /// allocated swap slot and return the new slots keyed by page number. Should any copy fail,
+    /// the slots allocated so far are freed so that swap space is left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `pid` - identifier of the process whose swapped pages are copied.
+    ///
+    /// # Errors
+    ///
+    /// An `UnknownProcess` error is returned if the process does not exist, while an `IOError` may
+    /// result from copying swap space, or a `SwapExhausted` error should no swap slots remain for
+    /// the copies.
+    fn copy_swap_slots(&mut self, pid: Pid) -> Result<HashMap<usize, usize>> {
+        let pages = &self
+            .processes
+            .get(&pid)
+            .ok_or(Error::UnknownProcess(pid))?
+            .pages;
+        let mut buffer = vec![0_u8; self.frames.frame_size as usize];
+        let mut copies = HashMap::new();
+        let mut result = Ok(());
+        for (page_number, page) in pages.iter() {
+            let Some(slot) = page.swap_slot else {
+                continue;
+            };
+            let Some(copy) = self.swap.allocate() else {
+                result = Err(Error::SwapExhausted);
+                break;
+            };
+            copies.insert(*page_number, copy);
+            let copied = self
+                .swap
+                .read(slot, &mut buffer)
+                .and_then(|_| self.swap.write(copy, &buffer));
+            if let Err(error) = copied {
+                result = Err(Error::IOError(error));
+                break;
+            }
+        }
+
+        if let Err(error) = result {
+            for copy in copies.into_values() {
+                self.swap.free(copy);
+            }
+            return Err(error);
+        }
+        Ok(copies)
+    }
+
+    /// Create a new shared memory segment and return its identifier. The segment is not mapped
+    /// by any process until attached with `attach_segment`. Segment identifiers are drawn from
+    /// the same sequence as process identifiers so that each segment receives its own region of
+    /// swap space.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - number of pages in the segment.
+    pub fn create_segment(&mut self, length: usize) -> usize {
+        let segment = self.next_pid;
+        self.segments.insert(segment, SharedSegment::build(length));
+        self.next_pid += 1;
+        segment
+    }
+
+    /// Map a shared memory segment into the address space of a process beginning at the provided
+    /// page number. Any existing pages within the range are replaced (see `map_area`). Every
+    /// process attached to a segment sees the writes made by the others.
+    ///
+    /// # Arguments
+    ///
+    /// * `pid` - identifier of the process attaching the segment.
+    /// * `segment` - identifier of the segment (see `create_segment`).
+    /// * `page_number` - logical page number at which the segment begins.
+    /// * `permissions` - the kinds of access to be permitted on the pages of the segment.
+    ///
+    /// # Errors
+    ///
+    /// An `UnknownProcess` or `UnknownSegment` error is returned if either does not exist.
+    pub fn attach_segment(
+        &mut self,
+        pid: Pid,
+        segment: usize,
+        page_number: usize,
+        permissions: Permissions,
+    ) -> Result<()> {
+        let length = self
+            .segments
+            .get(&segment)
+            .ok_or(Error::UnknownSegment(segment))?
+            .pages
+            .len();
+        self.map_area(
+            pid,
+            VirtualMemoryArea::new(
+                page_number,
+                length,
+                AreaKind::Shared {
+                    segment,
+                    page_offset: 0,
+                },
+                permissions,
+            ),
+        )
+    }
+
+    /// Open an additional file which may then be mapped into address spaces with `map_area`, and
+    /// return its identifier. The backing store is always open as file zero. Only files opened
+    /// as writable can receive the modified pages of shared mappings; writing back to any other
+    /// file results in an `IOError`.
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - path of the file to open.
+    /// * `writable` - whether modified pages may be written back to the file.
+    ///
+    /// # Panics
+    ///
+    /// The call will panic if the file does not exist (see `FileStore::build`).
+    pub fn open_file(&mut self, filename: &str, writable: bool) -> usize {
+        match writable {
+            true => self.add_file(FileStore::build_writable(filename)),
+            false => self.add_file(FileStore::build(filename)),
+        }
+    }
+
+    /// Add a store of any kind as an additional file which may be mapped with `map_area`, and
+    /// return its identifier (see `open_file`).
+    ///
+    /// # Arguments
+    ///
+    /// * `store` - the store holding the contents of the file.
+    pub fn add_file<S: BackingStore + 'static>(&mut self, store: S) -> usize {
+        self.files.push(Box::new(store));
+        self.files.len() - 1
+    }
+
+    /// Add a virtual memory area to the address space of a process, similar to `mmap` with
+    /// `MAP_FIXED` on POSIX systems. Any existing pages within the range of the new area are
+    /// unmapped first, discarding their contents.
+    ///
+    /// # Arguments
+    ///
+    /// * `pid` - identifier of the process receiving the area.
+    /// * `area` - the area to be mapped.
+    ///
+    /// # Errors
+    ///
+    /// An `UnknownProcess` error is returned if the process does not exist, while an
+    /// `UnknownSegment` or `UnknownFile` error is returned if the area refers to a segment or file
+    /// which does not exist. An `InvalidPageSize` error is returned if the area is mapped with
+    /// huge pages which are not a power of two in size, misaligned with the area, larger than the
+    /// frames available, or unsupported by the kind of area (only private file, anonymous, and
+    /// heap areas may use huge pages).
+    pub fn map_area(&mut self, pid: Pid, area: VirtualMemoryArea) -> Result<()> {
+        if !self.processes.contains_key(&pid) {
+            return Err(Error::UnknownProcess(pid));
+        }
+        let page_size = area.page_size;
+        let huge_kind = matches!(
+            area.kind,
+            AreaKind::File { shared: false, .. } | AreaKind::Anonymous | AreaKind::Heap
+        );
+        if page_size != 1
+            && (!page_size.is_power_of_two()
+                || !area.start.is_multiple_of(page_size)
+                || !area.length.is_multiple_of(page_size)
+                || page_size > self.usable_frames()
+                || !huge_kind)
+        {
+            return Err(Error::InvalidPageSize {
+                start: area.start,
+                page_size,
+            });
+        }
+        match area.kind {
+            AreaKind::Shared { segment, .. } if !self.segments.contains_key(&segment) => {
+                return Err(Error::UnknownSegment(segment))
+            }
+            AreaKind::File { file, .. } if file >= self.files.len() => {
+                return Err(Error::UnknownFile(file))
+            }
+            _ => (),
+        }
+        self.unmap_area(pid, area.start, area.length)?;
+        self.processes
+            .get_mut(&pid)
+            .expect("process should exist")
+            .areas
+            .insert(area);
+        Ok(())
+    }
+
+    /// Remove the logical pages `start..start + length` from the address space of a process,
+    /// similar to `munmap` on POSIX systems. Resident pages are released from their frames and
+    /// flushed from the TLB. Modified pages of shared memory segments and shared file mappings are
+    /// written back so their contents are not lost, while the contents of private pages are
+    /// discarded and any swap slots they hold are freed.
+    ///
+    /// # Arguments
+    ///
+    /// * `pid` - identifier of the process.
+    /// * `start` - the first logical page number to unmap.
+    /// * `length` - the number of pages to unmap.
+    ///
+    /// # Errors
+    ///
+    /// An `UnknownProcess` error is returned if the process does not exist, while an `IOError`
+    /// may result from writing back a shared page.
+    pub fn unmap_area(&mut self, pid: Pid, start: usize, length: usize) -> Result<()> {
+        let huge: Vec<usize> = match self.processes.get(&pid) {
+            Some(process) => (start..start + length)
+                .filter(|page_number| {
+                    process
+                        .pages
+                        .find(*page_number)
+                        .is_some_and(|page| page.huge.is_some())
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        huge.into_iter()
+            .for_each(|page_number| self.split_huge(pid, page_number));
+        let Process { pages, areas } = self
+            .processes
+            .get_mut(&pid)
+            .ok_or(Error::UnknownProcess(pid))?;
+
+        let mut shared_pages = Vec::new();
+        for page_number in start..start + length {
+            let page = match pages.remove(page_number) {
+                Some(page) => page,
+                None => continue,
+            };
+            if let Some(slot) = page.swap_slot {
+                self.swap.free(slot);
+            }
+            if !page.valid {
+                self.frames.forget(page.frame_index, (pid, page_number));
+                continue;
+            }
+            if page.prefetched {
+                self.tracker.prefetch_wasted += 1;
+            }
+            if page.pinned {
+                self.frames.unpin(page.frame_index);
+            }
+            self.frames.entries[page.frame_index]
+                .mappings
+                .retain(|other| *other != (pid, page_number));
+            if self.tlb.flush_element((pid, page_number)) {
+                self.tracker.tlb_flushes += 1;
+            }
+            if let Some((object, index)) = shared_page(areas, page_number) {
+                shared_pages.push((object, index, page));
+            }
+        }
+        areas.remove(start, length);
+
+        for (object, index, page) in shared_pages {
+            if page.dirty {
+                self.write_back_shared(object, index, page.frame_index)?;
+            }
+            if self.frames.entries[page.frame_index].mappings.is_empty() {
+                self.set_shared_frame(object, index, None);
+            }
+        }
+        self.tracker.pinned_frames = self.frames.pinned.len();
+        self.swap.record(&mut self.tracker);
+        Ok(())
+    }
+
+    /// Return the current process.
+    fn process(&self) -> &Process {
+        &self.processes[&self.current]
+    }
+
+    /// Return the page table of the current process as a mutable reference.
+    fn pages_mut(&mut self) -> &mut PageTable {
+        &mut self
+            .processes
+            .get_mut(&self.current)
+            .expect("current process should exist")
+            .pages
+    }
+
+    /// Ensure the page provided lies within a virtual memory area of the current process,
+    /// recording a segmentation fault when it does not. As is typical, a stack area grows downward
+    /// by a single page when the page immediately beneath it is referenced and not otherwise
+    /// mapped.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID.
+    ///
+    /// # Errors
+    ///
+    /// An `UnknownProcess` error is returned should the current process have been killed (see
+    /// `oom_kill`), while a `SegmentationFault` error is returned if the page lies outside every
+    /// area.
+    fn check_area(&mut self, page_number: usize) -> Result<()> {
+        let areas = &mut self
+            .processes
+            .get_mut(&self.current)
+            .ok_or(Error::UnknownProcess(self.current))?
+            .areas;
+        if areas.find(page_number).is_some() {
+            return Ok(());
+        }
+        if let Some(stack) = areas
+            .find_mut(page_number + 1)
+            .filter(|area| area.kind == AreaKind::Stack && area.start == page_number + 1)
+        {
+            stack.start -= 1;
+            stack.length += 1;
+            self.tracker.stack_growths += 1;
+            return Ok(());
+        }
+        self.tracker.segmentation_faults += 1;
+        Err(Error::SegmentationFault { page_number })
+    }
+
+    /// Ensure the kind of access requested is permitted on the page provided, recording a
+    /// protection fault when it is not.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID.
+    /// * `kind` - the kind of memory access attempted.
+    ///
+    /// # Errors
+    ///
+    /// A `ProtectionFault` error is returned if the page permissions forbid the access.
+    fn check_permissions(&mut self, page_number: usize, kind: AccessKind) -> Result<()> {
+        let process = self.process();
+        let permissions = match process.pages.find(page_number) {
+            Some(page) => page.permissions,
+            None => process
+                .areas
+                .find(page_number)
+                .map_or(Permissions::default(), |area| area.permissions),
+        };
+        if permissions.allows(kind) {
+            return Ok(());
+        }
+        match kind {
+            AccessKind::Read => self.tracker.read_protection_faults += 1,
+            AccessKind::Write(_) => self.tracker.write_protection_faults += 1,
+            AccessKind::Execute => self.tracker.execute_protection_faults += 1,
+        }
+        Err(Error::ProtectionFault { page_number, kind })
+    }
+
+    /// Provided a logical page number, return the index of the frame which holds it. The TLB is
+    /// consulted first, followed by the page table. Should the page not be resident, it is paged
+    /// in before returning, along with any pages the prefetcher suggests (see `prefetch`).
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID.
+    ///
+    /// # Errors
+    ///
+    /// An `UnknownProcess` error is returned should the current process have been killed (see
+    /// `oom_kill`). See `retrieve_frame` for other errors.
+    fn translate(&mut self, page_number: usize) -> Result<usize> {
+        if !self.processes.contains_key(&self.current) {
+            return Err(Error::UnknownProcess(self.current));
+        }
+        if let Some((frame_index, size)) = self.tlb.find((self.current, page_number)) {
+            self.tracker.tlb_hits += 1;
+            *self.tracker.tlb_hits_by_size.entry(size).or_default() += 1;
+            if size > 1 {
+                self.touch(page_number);
+            }
+            return Ok(frame_index);
+        }
+
+        self.walk(page_number);
+        let pages = &mut self
+            .processes
+            .get_mut(&self.current)
+            .expect("current process should exist")
+            .pages;
+        let frame_index = match pages.find_mut(page_number) {
+            Some(page) if page.valid => {
+                self.tracker.page_hits += 1;
+                if std::mem::take(&mut page.prefetched) {
+                    self.tracker.prefetch_hits += 1;
+                }
+                if std::mem::take(&mut page.clustered) {
+                    self.tracker.fault_around_io_saved += 1;
+                }
+                if std::mem::take(&mut page.untouched) {
+                    self.tracker.huge_fragmentation -= 1;
+                }
+                page.frame_index
+            }
+            _ => {
+                self.tracker.page_faults += 1;
+                let reads = self.tracker.file_reads + self.tracker.swap_ins;
+                let page_size = self
+                    .process()
+                    .areas
+                    .find(page_number)
+                    .map_or(1, |area| area.page_size);
+                let frame_index = match page_size {
+                    1 => self.retrieve_frame(page_number)?,
+                    size => self.retrieve_huge(page_number, size)?,
+                };
+                if self.tracker.file_reads + self.tracker.swap_ins > reads {
+                    self.tracker.hard_faults += 1;
+                }
+                self.map_resident_cluster(page_number);
+                self.prefetch(page_number)?;
+                frame_index
+            }
+        };
+        self.cache_translation(page_number, frame_index);
+        Ok(frame_index)
+    }
+
+    /// Cache the translation of a resident page of the current process in the TLB. A page
+    /// belonging to a huge page is cached as a single element covering the whole huge page.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID.
+    /// * `frame_index` - index of the frame holding the page.
+    fn cache_translation(&mut self, page_number: usize, frame_index: usize) {
+        let size = self
+            .process()
+            .pages
+            .find(page_number)
+            .and_then(|page| page.huge)
+            .unwrap_or(1);
+        let offset = page_number & (size - 1);
+        self.tlb.cache_element(
+            (self.current, page_number - offset),
+            frame_index - offset,
+            size,
+        );
+    }
+
+    /// Split the huge page containing the provided page of a process into base pages, each of
+    /// which keeps its frame. Any TLB element covering the huge page is flushed.
+    ///
+    /// # Arguments
+    ///
+    /// * `pid` - identifier of the process.
+    /// * `page_number` - logical page number of any page within the huge page.
+    fn split_huge(&mut self, pid: Pid, page_number: usize) {
+        let Some(pages) = self
+            .processes
+            .get_mut(&pid)
+            .map(|process| &mut process.pages)
+        else {
+            return;
+        };
+        let Some(size) = pages.find(page_number).and_then(|page| page.huge) else {
+            return;
+        };
+        let first_page = page_number & !(size - 1);
+        for member in first_page..first_page + size {
+            if let Some(page) = pages.find_mut(member) {
+                page.huge = None;
+                if std::mem::take(&mut page.untouched) {
+                    self.tracker.huge_fragmentation -= 1;
+                }
+            }
+        }
+        if self.tlb.flush_element((pid, first_page)) {
+            self.tracker.tlb_flushes += 1;
+        }
+    }
+
+    /// Record a reference to a page of the current process reached through a TLB element for a
+    /// huge page, which bypasses the page table.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID.
+    fn touch(&mut self, page_number: usize) {
+        if let Some(page) = self.pages_mut().find_mut(page_number) {
+            if std::mem::take(&mut page.untouched) {
+                self.tracker.huge_fragmentation -= 1;
+            }
+        }
+    }
+
+    /// Scan the address space of every process for aligned runs of `size_thp` base pages which
+    /// are fully populated and promote each to a transparent huge page, similar to `khugepaged`
+    /// on Linux. Only runs lying within a single private file, anonymous, or heap area of base
+    /// pages are eligible, and only while their frames are not shared with another process. No
+    /// runs are promoted while fewer than the `low` watermark of frames are free, and the scan
+    /// stops early should a run fail to be promoted because no block of frames can be claimed.
+    ///
+    /// # Errors
+    ///
+    /// See `promote`.
+    fn collapse_huge_pages(&mut self) -> Result<()> {
+        let size = self.size_thp;
+        let mut pids: Vec<Pid> = self.processes.keys().copied().collect();
+        pids.sort_unstable();
+        for pid in pids {
+            let areas: Vec<VirtualMemoryArea> = self.processes[&pid]
+                .areas
+                .iter()
+                .filter(|area| {
+                    area.page_size == 1
+                        && matches!(
+                            area.kind,
+                            AreaKind::File { shared: false, .. }
+                                | AreaKind::Anonymous
+                                | AreaKind::Heap
+                        )
+                })
+                .cloned()
+                .collect();
+            for area in areas {
+                let first = area.start.next_multiple_of(size);
+                for first_page in (first..area.end()).step_by(size) {
+                    if first_page + size > area.end()
+                        || self.frames.free.len() < self.watermarks.low
+                    {
+                        break;
+                    }
+                    if self.collapsible(pid, first_page, size) {
+                        match self.promote(pid, first_page, size) {
+                            Err(Error::AllFramesPinned | Error::SwapExhausted) => return Ok(()),
+                            result => result?,
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Determine whether every page of a run is resident as a base page in a frame mapped by no
+    /// other page.
+    ///
+    /// # Arguments
+    ///
+    /// * `pid` - identifier of the process.
+    /// * `first_page` - first logical page number of the run.
+    /// * `size` - number of pages in the run.
+    fn collapsible(&self, pid: Pid, first_page: usize, size: usize) -> bool {
+        let pages = &self.processes[&pid].pages;
+        (first_page..first_page + size).all(|member| {
+            pages.find(member).is_some_and(|page| {
+                page.valid
+                    && page.huge.is_none()
+                    && !page.pinned
+                    && self.frames.entries[page.frame_index].ref_count() == 1
+            })
+        })
+    }
+
+    /// Promote a run of resident base pages to a huge page by copying their contents into an
+    /// aligned run of contiguous frames (see `claim_block`). The frames previously holding the
+    /// pages are returned to the free pool beforehand, so they may form part of the new run.
+    /// Should no run be claimed, the frames are taken back from the free pool and the pages are
+    /// left as they were.
+    ///
+    /// # Arguments
+    ///
+    /// * `pid` - identifier of the process.
+    /// * `first_page` - first logical page number of the run.
+    /// * `size` - number of pages in the run.
+    ///
+    /// # Errors
+    ///
+    /// See `claim_block`.
+    fn promote(&mut self, pid: Pid, first_page: usize, size: usize) -> Result<()> {
+        let mut members = Vec::with_capacity(size);
+        let mut contents = Vec::with_capacity(size);
+        for member in first_page..first_page + size {
+            let frame_index = self.processes[&pid].pages.find(member).unwrap().frame_index;
+            let frame = &mut self.frames.entries[frame_index];
+            frame.mappings.clear();
+            contents.push(frame.buffer.clone());
+            members.push(frame_index);
+            self.frames.victimizer.remove(&frame_index);
+            self.frames.release(frame_index);
+            if self.tlb.flush_element((pid, member)) {
+                self.tracker.tlb_flushes += 1;
+            }
+        }
+
+        let first_frame = match self.claim_block(size) {
+            Ok(first_frame) => first_frame,
+            Err(err) => {
+                for (member, (frame_index, buffer)) in members.into_iter().zip(contents).enumerate()
+                {
+                    self.frames.free.retain(|index| *index != frame_index);
+                    let frame = &mut self.frames.entries[frame_index];
+                    frame.buffer = buffer;
+                    frame.mappings.push((pid, first_page + member));
+                    self.frames.victimizer.insert(frame_index, frame_index);
+                }
+                return Err(err);
+            }
+        };
+        for (offset, buffer) in contents.into_iter().enumerate() {
+            let frame = &mut self.frames.entries[first_frame + offset];
+            frame.buffer = buffer;
+            frame.mappings.push((pid, first_page + offset));
+            let page = self
+                .processes
+                .get_mut(&pid)
+                .and_then(|process| process.pages.find_mut(first_page + offset))
+                .expect("promoted pages should exist");
+            page.frame_index = first_frame + offset;
+            page.huge = Some(size);
+        }
+        self.tracker.thp_promotions += 1;
+        Ok(())
+    }
+
+    /// Split every huge page held by the provided frame into base pages so that the frame may be
+    /// reclaimed alone, rather than along with the rest of its huge page.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_index` - index of the target frame
+    fn demote(&mut self, frame_index: usize) {
+        for (pid, page_number) in self.frames.entries[frame_index].mappings.clone() {
+            self.split_huge(pid, page_number);
+        }
+        self.tracker.thp_demotions += 1;
+    }
+
+    /// Following a fault on the provided page, load the pages suggested by the prefetcher (if one
+    /// is configured). Frames for prefetched pages are claimed like any other, so prefetching may
+    /// evict pages in active use. Prefetched frames are then placed at the front of the
+    /// victimization queue: a prefetched page referenced before the next victimization is promoted
+    /// like any other, while the rest are victimized first and recorded as wasted. At most one
+    /// fewer page than the number of frames is prefetched, which guarantees the faulting page
+    /// itself survives.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID of the fault.
+    ///
+    /// # Errors
+    ///
+    /// See `retrieve_frame`.
+    fn prefetch(&mut self, page_number: usize) -> Result<()> {
+        let Some(prefetcher) = self.prefetcher.as_mut() else {
+            return Ok(());
+        };
+        let candidates = prefetcher.on_fault(self.current, page_number);
+        let limit = self.usable_frames().saturating_sub(1);
+        let mut prefetched = Vec::new();
+        for candidate in candidates {
+            if prefetched.len() == limit {
+                break;
+            }
+            if !self.prefetchable(candidate) {
+                continue;
+            }
+            prefetched.push(self.retrieve_frame(candidate)?);
+            self.pages_mut()
+                .find_mut(candidate)
+                .expect("retrieved page should exist")
+                .prefetched = true;
+            self.tracker.prefetches += 1;
+        }
+        self.frames.demote(&prefetched);
+        Ok(())
+    }
+
+    /// Determine whether a page of the current process may be prefetched. Only private pages which
+    /// are not yet resident and whose contents must be read (from swap space or a file) qualify,
+    /// excluding file pages beyond the end of the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID.
+    fn prefetchable(&self, page_number: usize) -> bool {
+        let process = self.process();
+        let Some(area) = process.areas.find(page_number) else {
+            return false;
+        };
+        let page = process.pages.find(page_number);
+        if area.page_size > 1
+            || page.is_some_and(|page| page.valid)
+            || shared_page(&process.areas, page_number).is_some()
+            || self.buffered(page_number)
+        {
+            return false;
+        }
+        if page.is_some_and(|page| page.swap_slot.is_some()) {
+            return true;
+        }
+        match (area.kind, area.source_page(page_number)) {
+            (AreaKind::File { file, .. }, Some(source_page)) => {
+                (source_page as u64) < self.files[file].size() / self.frames.frame_size
+            }
+            _ => false,
+        }
+    }
+
+    /// Determine whether a page of the current process which is not resident is still held by a
+    /// frame of the free pool or modified list (see `FrameTable::rescue`).
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID.
+    fn buffered(&self, page_number: usize) -> bool {
+        self.process().pages.find(page_number).is_some_and(|page| {
+            !page.valid
+                && self.frames.entries[page.frame_index].cached == Some((self.current, page_number))
+        })
+    }
+
+    /// Provided a faulting page of a private file area, read it together with its neighbors within
+    /// the aligned cluster of `fault_around` pages using a single read of the file. The run read
+    /// is the contiguous range of pages surrounding the fault which are neither resident nor held
+    /// in swap space and lie within both the area and the file, limited to the number of frames.
+    /// Every neighbor read is mapped and marked `clustered`, while the frame of the faulting page
+    /// is returned for the caller to map.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID of the fault.
+    /// * `area` - the file area containing the page.
+    /// * `file` - identifier of the file backing the area.
+    ///
+    /// # Errors
+    ///
+    /// An error will occur if the file cannot be read or a dirty victim cannot be written to swap
+    /// space.
+    fn read_cluster(
+        &mut self,
+        page_number: usize,
+        area: &VirtualMemoryArea,
+        file: usize,
+    ) -> Result<usize> {
+        let cluster_start = page_number & !(self.fault_around - 1);
+        let cluster_end = cluster_start + self.fault_around;
+        let limit = self.usable_frames().max(1);
+        let (mut first, mut end) = (page_number, page_number + 1);
+        while end - first < limit {
+            if first > cluster_start && self.clusterable(first - 1, area, file) {
+                first -= 1;
+            } else if end < cluster_end && self.clusterable(end, area, file) {
+                end += 1;
+            } else {
+                break;
+            }
+        }
+
+        let frame_size = self.frames.frame_size as usize;
+        let source_page = area
+            .source_page(first)
+            .expect("file areas should have a source page");
+        let mut buffer = vec![0_u8; (end - first) * frame_size];
+        self.files[file].read_at((source_page * frame_size) as u64, &mut buffer)?;
+        self.tracker.file_reads += 1;
+
+        let pid = self.current;
+        let mut fault_frame = None;
+        for (neighbor, contents) in (first..end).zip(buffer.chunks(frame_size)) {
+            let frame_index = self.claim_frame(neighbor)?;
+            self.frames.entries[frame_index]
+                .buffer
+                .copy_from_slice(contents);
+            if neighbor == page_number {
+                fault_frame = Some(frame_index);
+                continue;
+            }
+            self.frames.entries[frame_index]
+                .mappings
+                .push((pid, neighbor));
+            let page = self
+                .process()
+                .pages
+                .find(neighbor)
+                .cloned()
+                .unwrap_or(Page {
+                    permissions: area.permissions,
+                    ..Default::default()
+                });
+            self.pages_mut().insert(
+                neighbor,
+                Page {
+                    frame_index,
+                    valid: true,
+                    dirty: false,
+                    clustered: true,
+                    ..page
+                },
+            );
+            self.tracker.fault_around_pages += 1;
+        }
+        Ok(fault_frame.expect("cluster should contain the faulting page"))
+    }
+
+    /// Determine whether a neighboring page may be read as part of a fault-around cluster.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID of the neighbor.
+    /// * `area` - the file area containing the faulting page.
+    /// * `file` - identifier of the file backing the area.
+    fn clusterable(&self, page_number: usize, area: &VirtualMemoryArea, file: usize) -> bool {
+        let page = self.process().pages.find(page_number);
+        let file_pages = self.files[file].size() / self.frames.frame_size;
+        area.contains(page_number)
+            && !page.is_some_and(|page| page.valid || page.swap_slot.is_some())
+            && !self.buffered(page_number)
+            && area
+                .source_page(page_number)
+                .is_some_and(|source_page| (source_page as u64) < file_pages)
+    }
+
+    /// Following a fault, map every page within the aligned cluster of `fault_around` pages which
+    /// belongs to a shared segment or shared file mapping already held in memory by another
+    /// process. Such pages need no I/O at all, and mapping them now spares the minor faults their
+    /// first references would otherwise incur.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID of the fault.
+    fn map_resident_cluster(&mut self, page_number: usize) {
+        if self.fault_around <= 1 {
+            return;
+        }
+        let pid = self.current;
+        let cluster_start = page_number & !(self.fault_around - 1);
+        for neighbor in cluster_start..cluster_start + self.fault_around {
+            let process = self.process();
+            let page = process.pages.find(neighbor).cloned();
+            if neighbor == page_number || page.as_ref().is_some_and(|page| page.valid) {
+                continue;
+            }
+            let Some(frame_index) = shared_page(&process.areas, neighbor)
+                .and_then(|(object, index)| self.shared_frame(object, index))
+            else {
+                continue;
+            };
+            let permissions = process
+                .areas
+                .find(neighbor)
+                .expect("shared pages should lie within an area")
+                .permissions;
+            self.frames.entries[frame_index]
+                .mappings
+                .push((pid, neighbor));
+            self.pages_mut().insert(
+                neighbor,
+                Page {
+                    frame_index,
+                    valid: true,
+                    dirty: false,
+                    ..page.unwrap_or(Page {
+                        permissions,
+                        ..Default::default()
+                    })
+                },
+            );
+            self.tracker.fault_around_pages += 1;
+        }
+    }
+
+    /// Provided a logical page number, perform the modeled page table walk which follows a TLB
+    /// miss. The references needed to complete the walk, and those avoided by the
+    /// paging-structure cache, are recorded for later analysis.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID.
+    fn walk(&mut self, page_number: usize) {
+        let result = self.walker.walk(self.current, page_number);
+        self.tracker.page_walks += 1;
+        self.tracker.walk_references += result.references;
+        self.tracker.walk_references_saved += self.walker.levels() - result.references;
+        if let Some(level) = result.cached_level {
+            self.tracker.pwc_hits[level] += 1;
+        }
+    }
+
+    /// Prepare a resident page of the current process to be written. Pages marked copy-on-write
+    /// which still share their frame with another process receive a private copy of that frame,
+    /// and the index of the copy is returned. Otherwise, the page is simply made writable in
+    /// place and the original frame index is returned. The shared frame is pinned while the copy
+    /// is claimed so that it cannot be reclaimed from under the copy, and the page remains
+    /// copy-on-write should the claim fail.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID.
+    /// * `frame_index` - index of the frame currently mapped by the page.
+    ///
+    /// # Errors
+    ///
+    /// See `claim_frame`.
+    fn copy_on_write(&mut self, page_number: usize, frame_index: usize) -> Result<usize> {
+        let shared = self.frames.entries[frame_index].ref_count() > 1;
+        let page = self
+            .pages_mut()
+            .find_mut(page_number)
+            .expect("translated page should exist");
+        if !page.cow {
+            return Ok(frame_index);
+        }
+        if !shared {
+            page.cow = false;
+            page.merged = false;
+            return Ok(frame_index);
+        }
+        self.split_huge(self.current, page_number);
+
+        let mapping = (self.current, page_number);
+        self.frames.pin(frame_index);
+        let claimed = self.claim_frame(page_number);
+        self.frames.unpin(frame_index);
+        self.tracker.pinned_frames = self.frames.pinned.len();
+        let copy_index = match claimed {
+            Ok(copy_index) => copy_index,
+            Err(err) => {
+                self.release_orphan(frame_index);
+                return Err(err);
+            }
+        };
+        let contents = self.frames.entries[frame_index].buffer.clone();
+        self.frames.entries[frame_index]
+            .mappings
+            .retain(|other| *other != mapping);
+        let copy = &mut self.frames.entries[copy_index];
+        copy.buffer = contents;
+        copy.mappings = vec![mapping];
+
+        let page = self
+            .pages_mut()
+            .find_mut(page_number)
+            .expect("translated page should exist");
+        page.frame_index = copy_index;
+        page.valid = true;
+        page.cow = false;
+        let merged = std::mem::take(&mut page.merged);
+        if page.pinned {
+            self.frames.unpin(frame_index);
+            self.frames.pin(copy_index);
+        }
+        self.release_orphan(frame_index);
+        self.cache_translation(page_number, copy_index);
+        self.tracker.cow_copies += 1;
+        if merged {
+            self.tracker.ksm_unmerges += 1;
+        }
+        Ok(copy_index)
+    }
+
+    /// Scan every frame in use for identical contents and merge each duplicate into the frame it
+    /// duplicates, similar to KSM (kernel same-page merging) on Linux. Every page mapping either
+    /// frame is marked `merged` and copy-on-write, so that a write to any of them receives a
+    /// private copy once more (an unmerge), while the duplicate frame is returned to the free
+    /// pool. Only frames mapped exclusively by private base pages are scanned, leaving out shared
+    /// objects, huge pages, and pinned frames.
+    fn merge_same_pages(&mut self) {
+        let candidates: Vec<usize> = (0..self.frames.entries.len())
+            .filter(|index| self.frames.victimizer.contains_key(index) && self.mergeable(*index))
+            .collect();
+        for (duplicate, original) in self.frames.identical(candidates) {
+            let mappings = std::mem::take(&mut self.frames.entries[duplicate].mappings);
+            for (pid, page_number) in &mappings {
+                if self.tlb.flush_element((*pid, *page_number)) {
+                    self.tracker.tlb_flushes += 1;
+                }
+            }
+            let frame = &mut self.frames.entries[original];
+            frame.mappings.extend(mappings);
+            for (pid, page_number) in &frame.mappings {
+                let page = self
+                    .processes
+                    .get_mut(pid)
+                    .and_then(|process| process.pages.find_mut(*page_number))
+                    .expect("mapped pages should exist");
+                page.frame_index = original;
+                page.cow = true;
+                page.merged = true;
+            }
+            self.frames.victimizer.remove(&duplicate);
+            self.frames.release(duplicate);
+            self.tracker.ksm_merges += 1;
+        }
+    }
+
+    /// Determine whether a frame in use may be merged with others of identical contents, being
+    /// when every page mapping it is a resident private base page (see `merge_same_pages`).
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_index` - index of the target frame
+    fn mergeable(&self, frame_index: usize) -> bool {
+        let mappings = &self.frames.entries[frame_index].mappings;
+        !mappings.is_empty()
+            && mappings.iter().all(|(pid, page_number)| {
+                let Some(Process { pages, areas }) = self.processes.get(pid) else {
+                    return false;
+                };
+                pages.find(*page_number).is_some_and(|page| {
+                    page.valid
+                        && page.frame_index == frame_index
+                        && page.huge.is_none()
+                        && !page.pinned
+                        && shared_page(areas, *page_number).is_none()
+                })
+            })
+    }
+
+    /// Return the number of frames saved by same-page merging, being the number of resident
+    /// `merged` pages less the number of frames holding them.
+    fn merged_frames_saved(&self) -> usize {
+        let mut frames = HashSet::new();
+        let mut pages = 0;
+        for process in self.processes.values() {
+            for (_, page) in process.pages.iter() {
+                if page.valid && page.merged {
+                    frames.insert(page.frame_index);
+                    pages += 1;
+                }
+            }
+        }
+        pages - frames.len()
+    }
+
+    /// Return the number of frames which may be in use at once by pages which are not pinned,
+    /// being every frame save for those which direct reclaim keeps free and those pinned.
+    fn usable_frames(&self) -> usize {
+        (self.frames.entries.len() - self.watermarks.min).saturating_sub(self.frames.pinned.len())
+    }
+
+    /// Allocate a frame from the free pool. Should the allocation leave no more than the `min`
+    /// watermark of frames free, frames are first reclaimed synchronously (direct reclaim). Should
+    /// no frame be reclaimable, the OOM killer is invoked and the allocation retried (see
+    /// `oom_kill`).
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID of the page the frame is claimed for.
+    ///
+    /// # Errors
+    ///
+    /// An `OutOfMemory` error is returned should the OOM killer kill the current process. See
+    /// `oom_kill` and `reclaim_frame` for other errors.
+    fn claim_frame(&mut self, page_number: usize) -> Result<usize> {
+        loop {
+            match self.reclaim_and_allocate(page_number) {
+                Err(cause @ (Error::AllFramesPinned | Error::SwapExhausted)) => {
+                    self.oom_kill(cause)?
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Reclaim frames as described by `claim_frame` and allocate a frame from the free pool,
+    /// without invoking the OOM killer.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID of the page the frame is claimed for.
+    ///
+    /// # Errors
+    ///
+    /// An `AllFramesPinned` error is returned should no frame be free after reclaiming every frame
+    /// which is not pinned. See `reclaim_frame` for other errors.
+    fn reclaim_and_allocate(&mut self, page_number: usize) -> Result<usize> {
+        while self.frames.free.len() <= self.watermarks.min {
+            if self.reclaim_frame()? {
+                self.tracker.direct_reclaims += 1;
+            } else if !self.frames.modified.is_empty() {
+                self.write_modified()?;
+            } else {
+                break;
+            }
+        }
+        self.frames
+            .allocate(page_number)
+            .ok_or(Error::AllFramesPinned)
+    }
+
+    /// Handle an allocation which cannot be satisfied because every frame is pinned or swap space
+    /// is exhausted, similar to the OOM killer on Linux. Each process is scored by its resident set
+    /// size, and the process with the highest score (the newest among ties) is killed: every page
+    /// is unmapped, freeing its swap slots and pins and returning frames no longer mapped by any
+    /// other process to the free pool, after which the process is removed along with its page
+    /// table. The kill is recorded in `events`.
+    ///
+    /// # Arguments
+    ///
+    /// * `cause` - the error which prevented the allocation.
+    ///
+    /// # Errors
+    ///
+    /// The `cause` is returned should no process hold resident pages, while an `OutOfMemory` error
+    /// is returned should the current process be killed, leaving no process current. Accesses
+    /// then fail with an `UnknownProcess` error until another process is switched to. See
+    /// `unmap_area` for other errors.
+    fn oom_kill(&mut self, cause: Error) -> Result<()> {
+        let Some((resident_pages, pid)) = self
+            .processes
+            .iter()
+            .map(|(pid, process)| (process.resident_set_size(), *pid))
+            .filter(|(resident_pages, _)| *resident_pages > 0)
+            .max()
+        else {
+            return Err(cause);
+        };
+        let frames: Vec<usize> = self.processes[&pid]
+            .pages
+            .iter()
+            .filter(|(_, page)| page.valid)
+            .map(|(_, page)| page.frame_index)
+            .collect();
+        self.unmap_area(pid, 0, 1 << MASK_PAGE.count_ones())?;
+        for frame_index in frames {
+            self.release_orphan(frame_index);
+        }
+        self.processes.remove(&pid);
+        self.events.push(Event::OomKill {
+            pid,
+            resident_pages,
+        });
+        self.tracker.oom_kills += 1;
+        match pid == self.current {
+            true => Err(Error::OutOfMemory { pid }),
+            false => Ok(()),
+        }
+    }
+
+    /// Return a frame in use which no page maps any longer, such as after its last mapping is
+    /// removed outside of eviction, to the free pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_index` - index of the target frame
+    fn release_orphan(&mut self, frame_index: usize) {
+        if self.frames.entries[frame_index].mappings.is_empty()
+            && self.frames.victimizer.remove(&frame_index).is_some()
+        {
+            self.frames.release(frame_index);
+        }
+    }
+
+    /// Run the background reclaimer. Should fewer than the `low` watermark of frames be free,
+    /// frames are reclaimed until the `high` watermark is reached.
+    ///
+    /// # Errors
+    ///
+    /// See `reclaim_frame`.
+    fn reclaim_background(&mut self) -> Result<()> {
+        if self.frames.free.len() >= self.watermarks.low {
+            return Ok(());
+        }
+        while self.frames.free.len() + self.frames.modified.len() < self.watermarks.high {
+            match self.reclaim_frame() {
+                Ok(true) => self.tracker.background_reclaims += 1,
+                Ok(false) | Err(Error::SwapExhausted) => break,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    /// Select a frame with the victimization algorithm and page-out its contents, returning the
+    /// frame to the free pool. Using the reverse mapping of the victim, every page table entry
+    /// mapping it is invalidated and flushed from the TLB. Dirty pages are written back to swap
+    /// space first, while a page of a shared memory segment or shared file mapping is written back
+    /// once on behalf of all of its mappings (see `write_back_shared`). Frames which cannot be
+    /// paged-out for want of swap slots are passed over in favor of the next in the queue, such as
+    /// a clean frame which needs none. Returns `false` should no frame be in use.
+    ///
+    /// With page buffering enabled, a frame mapped by a single private page is buffered instead:
+    /// the frame keeps the page contents, and a dirty page is placed on the modified list rather
+    /// than written to swap space straight away. The modified list is written back once it grows
+    /// beyond `size_modified_list` frames (see `write_modified`).
+    ///
+    /// # Errors
+    ///
+    /// A `SwapExhausted` error is returned should every frame in use need more swap slots than
+    /// remain. An error will also occur if a dirty victim cannot be written to swap space.
+    fn reclaim_frame(&mut self) -> Result<bool> {
+        let Some(frame_index) = self.frames.victims().find(|index| self.swappable(*index)) else {
+            return match self.frames.victimizer.is_empty() {
+                true => Ok(false),
+                false => Err(Error::SwapExhausted),
+            };
+        };
+        self.frames.victimizer.remove(&frame_index);
+        if self.size_thp > 1 && self.huge_block(frame_index).len() > 1 {
+            self.demote(frame_index);
+        }
+        self.evict_block(frame_index)?;
+        Ok(true)
+    }
+
+    /// Allocate an aligned run of `size` contiguous frames to hold a huge page. The run chosen is
+    /// the one with the fewest frames in use among those without pinned frames, and those frames
+    /// are reclaimed regardless of their position within the victimization queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `size` - number of frames in the run.
+    ///
+    /// # Errors
+    ///
+    /// An `AllFramesPinned` error is returned should every run hold a pinned frame. See
+    /// `reclaim_frame` for other errors.
+    fn claim_block(&mut self, size: usize) -> Result<usize> {
+        let first_frame = (0..self.frames.entries.len() / size)
+            .map(|block| block * size)
+            .filter(|first| {
+                !(*first..first + size).any(|index| self.frames.pinned.contains_key(&index))
+            })
+            .min_by_key(|first| {
+                (*first..first + size)
+                    .filter(|index| !self.frames.free.contains(index))
+                    .count()
+            })
+            .ok_or(Error::AllFramesPinned)?;
+        let block = first_frame..first_frame + size;
+        for index in block.clone() {
+            if self.frames.victimizer.remove(&index).is_some() {
+                self.evict_block(index)?;
+                self.tracker.direct_reclaims += 1;
+            }
+        }
+        if self
+            .frames
+            .modified
+            .iter()
+            .any(|index| block.contains(index))
+        {
+            self.write_modified()?;
+        }
+        self.frames.allocate_block(first_frame, size);
+        Ok(first_frame)
+    }
+
+    /// Page-out a frame already removed from the victimization queue. Should the frame hold part
+    /// of a huge page, every other frame of the huge page is paged-out alongside it. Should too few
+    /// swap slots remain to page-out every frame, none are paged-out and the frame is returned to
+    /// the back of the victimization queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_index` - index of the target frame
+    ///
+    /// # Errors
+    ///
+    /// See `reclaim_frame`.
+    fn evict_block(&mut self, frame_index: usize) -> Result<()> {
+        let block = self.huge_block(frame_index);
+        if block
+            .clone()
+            .map(|index| self.swap_needed(index))
+            .sum::<usize>()
+            > self.swap.available()
+        {
+            self.frames.victimizer.insert(frame_index, frame_index);
+            return Err(Error::SwapExhausted);
+        }
+        for index in block {
+            if index == frame_index || self.frames.victimizer.remove(&index).is_some() {
+                self.evict(index)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Determine whether enough swap slots remain for a frame in use to be paged-out by
+    /// `reclaim_frame`, along with the rest of its huge page unless huge pages are demoted first.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_index` - index of the target frame
+    fn swappable(&self, frame_index: usize) -> bool {
+        let block = match self.size_thp > 1 {
+            true => frame_index..frame_index + 1,
+            false => self.huge_block(frame_index),
+        };
+        block.map(|index| self.swap_needed(index)).sum::<usize>() <= self.swap.available()
+    }
+
+    /// Provided a frame in use, return the number of swap slots which must be allocated to
+    /// page-out its contents, being one for each dirty private page mapping it which holds no
+    /// swap slot and is not buffered instead (see `evict`).
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_index` - index of the target frame
+    fn swap_needed(&self, frame_index: usize) -> usize {
+        let mappings = &self.frames.entries[frame_index].mappings;
+        let buffering = self.size_modified_list > 0 && mappings.len() == 1;
+        mappings
+            .iter()
+            .filter(|(pid, page_number)| {
+                let Some(Process { pages, areas }) = self.processes.get(pid) else {
+                    return false;
+                };
+                pages.find(*page_number).is_some_and(|page| {
+                    page.valid
+                        && page.frame_index == frame_index
+                        && page.dirty
+                        && page.swap_slot.is_none()
+                        && !(buffering && page.huge.is_none())
+                        && shared_page(areas, *page_number).is_none()
+                })
+            })
+            .count()
+    }
+
+    /// Provided a frame, return the run of frames holding the huge page it is part of, or the
+    /// frame alone should it hold no huge page.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_index` - index of the target frame
+    fn huge_block(&self, frame_index: usize) -> Range<usize> {
+        for (pid, page_number) in &self.frames.entries[frame_index].mappings {
+            let Some(page) = self.processes[pid].pages.find(*page_number) else {
+                continue;
+            };
+            if let (true, true, Some(size)) =
+                (page.valid, page.frame_index == frame_index, page.huge)
+            {
+                let first = frame_index - (page_number & (size - 1));
+                return first..first + size;
+            }
+        }
+        frame_index..frame_index + 1
+    }
+
+    /// Page-out the contents of a frame already removed from the victimization queue (see
+    /// `reclaim_frame`).
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_index` - index of the target frame
+    ///
+    /// # Errors
+    ///
+    /// See `reclaim_frame`.
+    fn evict(&mut self, frame_index: usize) -> Result<()> {
+        let frame = &mut self.frames.entries[frame_index];
+        let buffering = self.size_modified_list > 0 && frame.mappings.len() == 1;
+        let mut buffered = None;
+        let mut shared = None;
+        let mut shared_dirty = false;
+        for (pid, page_number) in std::mem::take(&mut frame.mappings) {
+            let Some(Process { pages, areas }) = self.processes.get_mut(&pid) else {
+                continue;
+            };
+            let page = match pages.find_mut(page_number) {
+                Some(page) if page.valid && page.frame_index == frame_index => page,
+                _ => continue,
+            };
+            page.valid = false;
+            let huge = page.huge.take();
+            if std::mem::take(&mut page.untouched) {
+                self.tracker.huge_fragmentation -= 1;
+            }
+            self.tracker.rmap_invalidations += 1;
+            if std::mem::take(&mut page.prefetched) {
+                self.tracker.prefetch_wasted += 1;
+            }
+            page.clustered = false;
+            page.merged = false;
+            match shared_page(areas, page_number) {
+                Some(key) => {
+                    shared = Some(key);
+                    shared_dirty |= page.dirty;
+                }
+                None if buffering && huge.is_none() => {
+                    buffered = Some(((pid, page_number), page.dirty))
+                }
+                None if page.dirty => {
+                    let slot = match page.swap_slot {
+                        Some(slot) => slot,
+                        None => self.swap.allocate().ok_or(Error::SwapExhausted)?,
+                    };
+                    self.swap.write(slot, &frame.buffer)?;
+                    page.swap_slot = Some(slot);
+                    self.tracker.page_outs += 1;
+                }
+                None => (),
+            }
+            page.dirty &= buffered.is_some();
+            if self.tlb.flush_element((pid, page_number)) {
+                self.tracker.tlb_flushes += 1;
+            }
+        }
+
+        if let Some((object, index)) = shared {
+            self.set_shared_frame(object, index, None);
+            if shared_dirty {
+                self.write_back_shared(object, index, frame_index)?;
+            }
+        }
+        match buffered {
+            Some((page, dirty)) => {
+                self.frames.entries[frame_index].cached = Some(page);
+                match dirty {
+                    true => self.frames.modified.push_back(frame_index),
+                    false => self.frames.release(frame_index),
+                }
+                if self.frames.modified.len() > self.size_modified_list {
+                    self.write_modified()?;
+                }
+            }
+            None => self.frames.release(frame_index),
+        }
+        Ok(())
+    }
+
+    /// Write every frame of the modified list to swap space and move it to the free pool. Each
+    /// frame keeps its contents, so the page it holds remains eligible for rescue until the frame
+    /// is allocated anew.
+    ///
+    /// # Errors
+    ///
+    /// An error will occur if a frame cannot be written to swap space, including when no swap
+    /// slots remain (`SwapExhausted`).
+    fn write_modified(&mut self) -> Result<()> {
+        while let Some(&frame_index) = self.frames.modified.front() {
+            let frame = &self.frames.entries[frame_index];
+            let (pid, page_number) = frame.cached.expect("modified frames should hold a page");
+            let page = self
+                .processes
+                .get_mut(&pid)
+                .and_then(|process| process.pages.find_mut(page_number))
+                .expect("modified pages should exist");
+            let slot = match page.swap_slot {
+                Some(slot) => slot,
+                None => self.swap.allocate().ok_or(Error::SwapExhausted)?,
+            };
+            self.swap.write(slot, &frame.buffer)?;
+            page.swap_slot = Some(slot);
+            page.dirty = false;
+            self.tracker.page_outs += 1;
+            self.frames.modified.pop_front();
+            self.frames.release(frame_index);
+        }
+        Ok(())
+    }
+
+    /// Provided a page of a shared object, return the index of the frame holding it should the
+    /// page be resident.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - the segment or file backing the page.
+    /// * `index` - the page index within the object.
+    fn shared_frame(&self, object: SharedObject, index: usize) -> Option<usize> {
+        match object {
+            SharedObject::Segment(segment) => self.segments[&segment].pages[index].frame_index,
+            SharedObject::File(file) => self.file_pages.get(&(file, index)).copied(),
+        }
+    }
+
+    /// Record the frame holding a page of a shared object, or that the page is no longer resident
+    /// when `frame_index` is `None`.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - the segment or file backing the page.
+    /// * `index` - the page index within the object.
+    /// * `frame_index` - index of the frame now holding the page (if any).
+    fn set_shared_frame(&mut self, object: SharedObject, index: usize, frame_index: Option<usize>) {
+        match (object, frame_index) {
+            (SharedObject::Segment(segment), _) => {
+                self.segments
+                    .get_mut(&segment)
+                    .expect("mapped segment should exist")
+                    .pages[index]
+                    .frame_index = frame_index;
+            }
+            (SharedObject::File(file), Some(frame_index)) => {
+                self.file_pages.insert((file, index), frame_index);
+            }
+            (SharedObject::File(file), None) => {
+                self.file_pages.remove(&(file, index));
+            }
+        }
+    }
+
+    /// Write the contents of a frame holding a page of a shared object back to where the page is
+    /// preserved. Segment pages are written to swap space, while file pages are written to the
+    /// file itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - the segment or file backing the page.
+    /// * `index` - the page index within the object.
+    /// * `frame_index` - index of the frame holding the page.
+    ///
+    /// # Errors
+    ///
+    /// An error will occur if the write fails, including when the file is not writable.
+    fn write_back_shared(
+        &mut self,
+        object: SharedObject,
+        index: usize,
+        frame_index: usize,
+    ) -> Result<()> {
+        let buffer = &self.frames.entries[frame_index].buffer;
+        match object {
+            SharedObject::Segment(segment) => {
+                let shared = &mut self
+                    .segments
+                    .get_mut(&segment)
+                    .expect("mapped segment should exist")
+                    .pages[index];
+                let slot = match shared.swap_slot {
+                    Some(slot) => slot,
+                    None => self.swap.allocate().ok_or(Error::SwapExhausted)?,
+                };
+                self.swap.write(slot, buffer)?;
+                shared.swap_slot = Some(slot);
+                self.tracker.page_outs += 1;
+            }
+            SharedObject::File(file) => {
+                self.files[file].write(index as u64, buffer)?;
+                self.tracker.file_write_backs += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a page of a shared object into the provided frame. Segment pages previously written
+    /// back are read from swap space and are otherwise zero-filled, while file pages are read
+    /// from the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - the segment or file backing the page.
+    /// * `index` - the page index within the object.
+    /// * `source` - for file pages, the page read in place of `index` (see `file_page`), with
+    ///   `None` requesting a zero-filled page.
+    /// * `frame_index` - index of the frame receiving the page.
+    ///
+    /// # Errors
+    ///
+    /// An error will occur if the read fails.
+    fn load_shared(
+        &mut self,
+        object: SharedObject,
+        index: usize,
+        source: Option<u64>,
+        frame_index: usize,
+    ) -> Result<()> {
+        let buffer = &mut self.frames.entries[frame_index].buffer;
+        match object {
+            SharedObject::Segment(segment) => {
+                match self.segments[&segment].pages[index].swap_slot {
+                    Some(slot) => {
+                        match self.swap.compressed(slot) {
+                            true => self.tracker.zswap_faults += 1,
+                            false => self.tracker.swap_ins += 1,
+                        }
+                        self.swap.read(slot, buffer)?;
+                    }
+                    None => buffer.fill(0),
+                }
+            }
+            SharedObject::File(file) => match source {
+                Some(page) => {
+                    self.files[file].read(page, buffer)?;
+                    self.tracker.file_reads += 1;
+                }
+                None => {
+                    buffer.fill(0);
+                    self.tracker.zero_fill_faults += 1;
+                }
+            },
+        }
+        Ok(())
+    }
+
+    /// Provided a page of an open file, return the page to be read from the file in its place.
+    /// Pages lying entirely within the file are read as-is, while the configured `OutOfRange`
+    /// policy decides the fate of any other page: `None` is returned should the page be
+    /// zero-filled instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - identifier of the file (see `open_file`).
+    /// * `page_number` - page number within the file.
+    ///
+    /// # Errors
+    ///
+    /// A `PageOutOfRange` error is returned if the page lies beyond the end of the file and the
+    /// policy is `OutOfRange::Error`, or the file is too small to wrap around.
+    fn file_page(&mut self, file: usize, page_number: usize) -> Result<Option<u64>> {
+        let store_size = self.files[file].size();
+        let whole_pages = store_size / self.frames.frame_size;
+        let page = page_number as u64;
+        if page < whole_pages {
+            return Ok(Some(page));
+        }
+        self.tracker.out_of_range_pages += 1;
+        match self.out_of_range {
+            OutOfRange::ZeroFill => Ok(None),
+            OutOfRange::Wrap if whole_pages > 0 => Ok(Some(page % whole_pages)),
+            _ => Err(Error::PageOutOfRange {
+                page_number,
+                store_size,
+            }),
+        }
+    }
+
+    /// Provided a logical page number, allocate a free frame and read the data referenced by the
+    /// page into the frame buffer to maintain the illusion of unmanaged memory access from the
+    /// perspective of the process. A dirty victim is written back to swap space beforehand, and
+    /// pages previously written back are read from swap rather than the backing store.
+    ///
+    /// Otherwise, the virtual memory area containing the page determines its origin. Pages of
+    /// file areas are read from the mapped file, while pages of anonymous, stack, and heap areas
+    /// are zero-filled without reading anything. Pages of shared memory segments are also
+    /// zero-filled when first referenced. Should another process already hold a page of a shared
+    /// segment or shared file mapping in memory, its frame is mapped without reading anything at
+    /// all (a minor fault). With fault-around enabled, a page of a private file area is read
+    /// together with its neighbors (see `read_cluster`). With page buffering enabled, a page still
+    /// held by a frame of the free pool or modified list is rescued without reading anything (a
+    /// soft fault).
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID.
+    ///
+    /// # Errors
+    ///
+    /// An error will occur if the storage read operation fails, a dirty victim cannot be written to
+    /// swap space, or the page lies past the end of its file (see `file_page`). The error value is
+    /// returned to the caller in the form of the `Error` enum variant.
+    fn retrieve_frame(&mut self, page_number: usize) -> Result<usize> {
+        let pid = self.current;
+        let process = self.process();
+        let area = process
+            .areas
+            .find(page_number)
+            .cloned()
+            .expect("accessed page should lie within an area");
+        let page = process.pages.find(page_number).cloned().unwrap_or(Page {
+            permissions: area.permissions,
+            ..Default::default()
+        });
+        let shared = shared_page(&process.areas, page_number)
+            .map(|(object, index)| (object, index, self.shared_frame(object, index)));
+
+        let mut dirty = false;
+        let frame_index = match shared {
+            Some((_, _, Some(frame_index))) => {
+                self.tracker.minor_faults += 1;
+                frame_index
+            }
+            None if self.frames.rescue(page.frame_index, (pid, page_number)) => {
+                self.tracker.soft_faults += 1;
+                dirty = page.dirty;
+                page.frame_index
+            }
+            Some((object, index, None)) => {
+                let source = match object {
+                    SharedObject::File(file) => self.file_page(file, index)?,
+                    SharedObject::Segment(_) => None,
+                };
+                let frame_index = self.claim_frame(page_number)?;
+                self.load_shared(object, index, source, frame_index)?;
+                self.set_shared_frame(object, index, Some(frame_index));
+                frame_index
+            }
+            None => {
+                let source = self.private_source(&area, &page, page_number)?;
+                match (page.swap_slot, source) {
+                    (None, Some((file, Some(source_page))))
+                        if self.fault_around > 1
+                            && area.source_page(page_number) == Some(source_page as usize) =>
+                    {
+                        self.read_cluster(page_number, &area, file)?
+                    }
+                    (swap_slot, source) => {
+                        let frame_index = self.claim_frame(page_number)?;
+                        self.load_private(frame_index, swap_slot, source)?;
+                        frame_index
+                    }
+                }
+            }
+        };
+
+        self.frames.entries[frame_index]
+            .mappings
+            .push((pid, page_number));
+        self.pages_mut().insert(
+            page_number,
+            Page {
+                frame_index,
+                valid: true,
+                dirty,
+                ..page
+            },
+        );
+
+        Ok(frame_index)
+    }
+
+    /// Provided a page of a huge page area, page-in every page of the huge page containing it
+    /// using a contiguous run of frames (see `claim_block`), and return the frame holding the
+    /// page provided. Each page is read from swap space or the mapped file, or zero-filled, as
+    /// with `retrieve_frame`. Should another page of the huge page still be resident or buffered,
+    /// such as after the huge page was split, the page is instead paged-in alone. Every page other
+    /// than the one provided is marked `untouched`. Should no run be reclaimable, the OOM killer is
+    /// invoked and the allocation retried (see `oom_kill`).
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number/ID.
+    /// * `size` - size of the huge page in base pages.
+    ///
+    /// # Errors
+    ///
+    /// See `retrieve_frame`.
+    fn retrieve_huge(&mut self, page_number: usize, size: usize) -> Result<usize> {
+        let pid = self.current;
+        let first_page = page_number & !(size - 1);
+        let area = self
+            .process()
+            .areas
+            .find(page_number)
+            .cloned()
+            .expect("accessed page should lie within an area");
+        let members: Vec<Page> = (first_page..first_page + size)
+            .map(|member| {
+                self.process().pages.find(member).cloned().unwrap_or(Page {
+                    permissions: area.permissions,
+                    ..Default::default()
+                })
+            })
+            .collect();
+        if (first_page..first_page + size)
+            .zip(&members)
+            .any(|(member, page)| page.valid || self.buffered(member))
+        {
+            return self.retrieve_frame(page_number);
+        }
+
+        let mut sources = Vec::with_capacity(size);
+        for (member, page) in (first_page..).zip(&members) {
+            sources.push(self.private_source(&area, page, member)?);
+        }
+        let first_frame = loop {
+            match self.claim_block(size) {
+                Err(cause @ (Error::AllFramesPinned | Error::SwapExhausted)) => {
+                    self.oom_kill(cause)?
+                }
+                result => break result?,
+            }
+        };
+        for (offset, (page, source)) in members.into_iter().zip(sources).enumerate() {
+            let frame_index = first_frame + offset;
+            self.load_private(frame_index, page.swap_slot, source)?;
+            self.frames.entries[frame_index]
+                .mappings
+                .push((pid, first_page + offset));
+            self.pages_mut().insert(
+                first_page + offset,
+                Page {
+                    frame_index,
+                    valid: true,
+                    dirty: false,
+                    huge: Some(size),
+                    untouched: first_page + offset != page_number,
+                    ..page
+                },
+            );
+        }
+        self.tracker.huge_fragmentation += size - 1;
+        Ok(first_frame + page_number - first_page)
+    }
+
+    /// Provided a private page which is not resident, return the file and page within it to be
+    /// read in its place should the page be read from a file rather than from swap space or
+    /// zero-filled (see `file_page`).
+    ///
+    /// # Arguments
+    ///
+    /// * `area` - the area containing the page.
+    /// * `page` - the page table entry of the page.
+    /// * `page_number` - logical page number/ID.
+    ///
+    /// # Errors
+    ///
+    /// See `file_page`.
+    fn private_source(
+        &mut self,
+        area: &VirtualMemoryArea,
+        page: &Page,
+        page_number: usize,
+    ) -> Result<Option<(usize, Option<u64>)>> {
+        match (page.swap_slot, area.kind) {
+            (None, AreaKind::File { file, .. }) => {
+                let source_page = area
+                    .source_page(page_number)
+                    .expect("file areas should have a source page");
+                Ok(Some((file, self.file_page(file, source_page)?)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Fill a frame with the contents of a private page, reading from swap space when the page
+    /// holds a swap slot, otherwise from the source file (see `private_source`), and otherwise
+    /// zero-filling it. A swap slot held by the compressed pool is decompressed without reading
+    /// swap space, which counts as a zswap fault rather than a swap-in.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_index` - index of the frame receiving the page.
+    /// * `swap_slot` - the swap slot of the page (if any).
+    /// * `source` - the file and page within it to be read (if any).
+    ///
+    /// # Errors
+    ///
+    /// An error will occur if the storage read operation fails.
+    fn load_private(
+        &mut self,
+        frame_index: usize,
+        swap_slot: Option<usize>,
+        source: Option<(usize, Option<u64>)>,
+    ) -> Result<()> {
+        let frame = &mut self.frames.entries[frame_index];
+        match (swap_slot, source) {
+            (Some(slot), _) => {
+                match self.swap.compressed(slot) {
+                    true => self.tracker.zswap_faults += 1,
+                    false => self.tracker.swap_ins += 1,
+                }
+                self.swap.read(slot, &mut frame.buffer)?;
+            }
+            (None, Some((file, Some(source_page)))) => {
+                self.files[file].read(source_page, &mut frame.buffer)?;
+                self.tracker.file_reads += 1;
+            }
+            (None, Some((_, None))) | (None, None) => {
+                frame.buffer.fill(0);
+                self.tracker.zero_fill_faults += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Provided the virtual memory areas of a process and a logical page number, return the shared
+/// object and page index within it which the page maps (if any). Pages of shared memory segments
+/// and shared file mappings are shared objects, whereas private file mappings are not.
+///
+/// # Arguments
+///
+/// * `areas` - the virtual memory areas of the process.
+/// * `page_number` - logical page number/ID.
+fn shared_page(areas: &AreaMap, page_number: usize) -> Option<(SharedObject, usize)> {
+    let area = areas.find(page_number)?;
+    let object = match area.kind {
+        AreaKind::Shared { segment, .. } => SharedObject::Segment(segment),
+        AreaKind::File {
+            file, shared: true, ..
+        } => SharedObject::File(file),
+        _ => return None,
+    };
+    Some((object, area.source_page(page_number)?))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::storage::MemoryStore;
+
+    const SIZE_FRAME: u64 = 256;
+    const SIZE_TABLE: usize = 256;
+
+    #[cfg(test)]
+    mod permissions_tests {
+
+        use super::*;
+
+        #[test]
+        fn allows() {
+            let permissions = Permissions::READ_EXECUTE;
+            assert!(permissions.allows(AccessKind::Read));
+            assert!(permissions.allows(AccessKind::Execute));
+            assert!(!permissions.allows(AccessKind::Write(0)));
+            assert!(!Permissions::READ_WRITE.allows(AccessKind::Execute));
+            assert_eq!(Permissions::default(), Permissions::ALL);
+        }
+    }
+
+    #[cfg(test)]
+    mod page_tests {
+
+        use super::*;
+
+        #[test]
+        fn new() {
+            let page = Page {
+                frame_index: 0xF,
+                valid: false,
+                ..Default::default()
+            };
+            assert!(!page.valid);
+            assert_eq!(page.frame_index, 0xF);
         }
     }
 
@@ -428,6 +3196,7 @@ mod tests {
                     Page {
                         frame_index: x,
                         valid: true,
+                        ..Default::default()
                     },
                 )
             });
@@ -440,7 +3209,7 @@ mod tests {
             let table = PageTable::build();
 
             // assert
-            assert!(table.0.len() == 0)
+            assert!(table.0.is_empty())
         }
 
         #[test]
@@ -482,6 +3251,7 @@ mod tests {
             let new_page = Page {
                 frame_index,
                 valid: true,
+                ..Default::default()
             };
 
             // act
@@ -492,7 +3262,8 @@ mod tests {
                 table.find(page_id),
                 Some(&Page {
                     frame_index,
-                    valid: true
+                    valid: true,
+                    ..Default::default()
                 })
             );
         }
@@ -522,9 +3293,9 @@ mod tests {
             let mut table = FrameTable::build(TEST_TABLE_SIZE, TEST_FRAME_SIZE);
 
             (0..TEST_TABLE_SIZE).for_each(|x| {
-                let frame_number = table.allocate();
+                let frame_number = table.allocate(x).unwrap();
                 let frame = &mut table.entries[frame_number];
-                frame.associated_page_id = x;
+                frame.mappings.push((0, x));
                 frame[0] = x as u8;
             });
             table
@@ -540,9 +3311,32 @@ mod tests {
         #[test]
         fn allocate() {
             let mut ft = make_standard_table();
-            assert_eq!(ft.victimizer.front().unwrap().0, &0);
-            ft.allocate();
+            assert_eq!(ft.allocate(0), None);
+            assert_eq!(ft.victims().next(), Some(0));
+            ft.victimizer.remove(&0);
+            ft.release(0);
+            assert_eq!(ft.allocate(0), Some(0));
             assert_eq!(ft.victimizer.front().unwrap().0, &1);
+            assert_eq!(ft.victimizer.back().unwrap().0, &0);
+        }
+
+        #[test]
+        fn coloring() {
+            assert_eq!("bin-hopping".parse(), Ok(FrameColoring::BinHopping));
+            assert!("random".parse::<FrameColoring>().is_err());
+
+            let mut ft = FrameTable {
+                coloring: FrameColoring::PageColoring,
+                colors: 4,
+                ..FrameTable::build(8, TEST_FRAME_SIZE)
+            };
+            assert_eq!(ft.allocate(6), Some(2));
+            assert_eq!(ft.allocate(2), Some(6));
+            assert_eq!(ft.allocate(2), Some(0));
+
+            ft.coloring = FrameColoring::BinHopping;
+            let frames: Vec<_> = (0..4).map(|_| ft.allocate(0).unwrap()).collect();
+            assert_eq!(frames, vec![4, 1, 3, 7]);
         }
 
         #[test]
@@ -552,6 +3346,24 @@ mod tests {
             ft.reference(0);
             assert_eq!(ft.victimizer.back().unwrap().0, &0);
         }
+
+        #[test]
+        fn identical() {
+            let mut ft = make_standard_table();
+            ft.entries[2][0] = 0;
+            ft.entries[3][0] = 1;
+            assert_eq!(ft.identical(0..TEST_TABLE_SIZE), vec![(2, 0), (3, 1)]);
+            assert_eq!(ft.identical([3, 1]), vec![(1, 3)]);
+        }
+
+        #[test]
+        fn demote() {
+            let mut ft = make_standard_table();
+            ft.demote(&[3, 2]);
+            let order: Vec<usize> = ft.victimizer.keys().copied().collect();
+            assert_eq!(order[..4], [3, 2, 0, 1]);
+            assert_eq!(order.len(), ft.entries.len());
+        }
     }
 
     #[cfg(test)]
@@ -574,12 +3386,912 @@ mod tests {
             let max = 5;
 
             (min..max).for_each(|x| {
-                assert!(tlb.find(x).is_none());
-                tlb.cache_element(x, x);
-                assert!(tlb.find(x).is_some());
+                assert!(tlb.find((0, x)).is_none());
+                tlb.cache_element((0, x), x, 1);
+                assert!(tlb.find((0, x)).is_some());
+            });
+
+            assert!(tlb.find((0, max)).is_none());
+            assert!(tlb.find((1, max - 1)).is_none());
+        }
+
+        #[test]
+        fn huge_elements() {
+            let mut tlb = TLB::build(SIZE_TEST);
+            tlb.cache_element((0, 0x10), 32, 16);
+            tlb.cache_element((0, 0x20), 4, 1);
+            assert_eq!(tlb.find((0, 0x1F)), Some((47, 16)));
+            assert_eq!(tlb.find((0, 0x20)), Some((4, 1)));
+            assert!(tlb.find((0, 0x21)).is_none());
+            assert!(tlb.flush_element((0, 0x13)));
+            assert!(tlb.find((0, 0x10)).is_none());
+        }
+    }
+
+    #[cfg(test)]
+    mod virtual_memory_tests {
+
+        use super::*;
+        use clap::Parser;
+
+        fn standard_config(test_name: &str) -> Config {
+            let mut config = Config::parse_from(["virtual_memory_sim"]);
+            config.size_table = SIZE_TABLE as u32;
+            config.size_frame = SIZE_FRAME as u32;
+            config.file_swap = std::env::temp_dir()
+                .join(format!("virtual_memory_tests_{}.bin", test_name))
+                .to_str()
+                .unwrap()
+                .to_string();
+            config
+        }
+
+        fn standard_memory(config: &Config) -> VirtualMemory {
+            VirtualMemory::build(config, FileStore::build(&config.file_storage))
+        }
+
+        #[test]
+        fn memory_store() {
+            let contents = (0..SIZE_FRAME * 4).map(|x| (x % 251) as u8).collect();
+            let config = standard_config("memory_store");
+            let mut vm = VirtualMemory::build(&config, MemoryStore::new(contents));
+            let result = vm.access(VirtualAddress::from(0x0302)).unwrap();
+            assert_eq!(result.value, ((0x0302 % 251) as u8) as i8);
+        }
+
+        #[test]
+        fn out_of_range() {
+            let contents: Vec<u8> = (0..SIZE_FRAME * 5 / 2)
+                .map(|x| (x / SIZE_FRAME + 1) as u8)
+                .collect();
+            let mut config = standard_config("out_of_range");
+            let mut vm = VirtualMemory::build(&config, MemoryStore::new(contents.clone()));
+            assert_eq!(vm.access(VirtualAddress::from(0x0100)).unwrap().value, 2);
+            assert!(matches!(
+                vm.access(VirtualAddress::from(0x0200)),
+                Err(Error::PageOutOfRange {
+                    page_number: 2,
+                    store_size: 640
+                })
+            ));
+            assert_eq!(vm.tracker.out_of_range_pages, 1);
+
+            config.out_of_range = String::from("zero-fill");
+            let mut vm = VirtualMemory::build(&config, MemoryStore::new(contents.clone()));
+            assert_eq!(vm.access(VirtualAddress::from(0x0301)).unwrap().value, 0);
+            assert_eq!(vm.tracker.zero_fill_faults, 1);
+
+            config.out_of_range = String::from("wrap");
+            let mut vm = VirtualMemory::build(&config, MemoryStore::new(contents));
+            assert_eq!(vm.access(VirtualAddress::from(0x0301)).unwrap().value, 2);
+            assert_eq!(vm.tracker.out_of_range_pages, 1);
+        }
+
+        #[test]
+        fn access() {
+            let mut vm = standard_memory(&standard_config("access"));
+            let result = vm.access(VirtualAddress::from(16916)).unwrap();
+            assert_eq!(result.physical_address, 20);
+            assert_eq!(result.value, 0);
+            vm.access(VirtualAddress::from(16916)).unwrap();
+            assert_eq!(vm.tracker.attempted_memory_accesses, 2);
+            assert_eq!(vm.tracker.tlb_hits, 1);
+        }
+
+        #[test]
+        fn data_cache() {
+            let mut vm = standard_memory(&standard_config("data_cache"));
+            for address in [0x0000, 0x0004, 0x0100, 0x0000] {
+                vm.access(VirtualAddress::from(address)).unwrap();
+            }
+            assert_eq!((vm.tracker.l1_hits, vm.tracker.l1_misses), (2, 2));
+            assert_eq!((vm.tracker.l2_hits, vm.tracker.l2_misses), (0, 2));
+        }
+
+        #[test]
+        fn frame_coloring() {
+            let mut conflict_misses = Vec::new();
+            for coloring in ["none", "page-coloring", "bin-hopping"] {
+                let mut config = standard_config("frame_coloring");
+                config.size_table = 8;
+                config.size_l1 = 0;
+                config.size_l2 = 1024;
+                config.l2_ways = 1;
+                config.frame_coloring = String::from(coloring);
+                let mut vm = standard_memory(&config);
+                for page_number in (0..8).chain([4, 1, 5, 2, 3, 6, 7]) {
+                    vm.access(VirtualAddress::from(page_number << 8)).unwrap();
+                }
+                vm.reclaim_interval = 1;
+                vm.watermarks.low = 4;
+                vm.watermarks.high = 4;
+                vm.access(VirtualAddress::from(0x0200)).unwrap();
+                assert_eq!(vm.frames.free, [0, 4, 1, 5]);
+
+                let setup_misses = vm.tracker.l2_conflict_misses;
+                for _ in 0..8 {
+                    vm.access(VirtualAddress::from(0x0800)).unwrap();
+                    vm.access(VirtualAddress::from(0x0900)).unwrap();
+                }
+                conflict_misses.push(vm.tracker.l2_conflict_misses - setup_misses);
+                let label = format!("frame_coloring:           {}\n", coloring);
+                assert!(vm.tracker.to_string().contains(&label));
+            }
+            assert_eq!(conflict_misses, vec![16, 2, 2]);
+        }
+
+        #[test]
+        fn virtual_clock() {
+            let mut config = standard_config("virtual_clock");
+            config.latency_tlb = 1;
+            config.latency_walk_level = 10;
+            config.latency_memory = 100;
+            config.latency_fault = 1000;
+            config.ssd_latency_ns = 5000;
+            config.ssd_bandwidth = 256;
+            let mut vm = standard_memory(&config);
+            vm.access(VirtualAddress::from(0x0000)).unwrap();
+            assert_eq!(vm.tracker.simulated_time_ns, 7121);
+            assert_eq!(vm.tracker.fault_service_ns, 7000);
+            vm.access(VirtualAddress::from(0x0010)).unwrap();
+            assert_eq!(vm.tracker.simulated_time_ns, 7222);
+            assert_eq!(vm.tracker.effective_access_time(), 3611.0);
+
+            let mut config = standard_config("virtual_clock_queue");
+            config.size_table = 2;
+            config.reclaim_interval = 2;
+            config.watermark_low = 2;
+            config.watermark_high = 2;
+            config.latency_fault = 0;
+            config.ssd_latency_ns = 5000;
+            config.ssd_bandwidth = 256;
+            let mut vm = standard_memory(&config);
+            vm.write(VirtualAddress::from(0x0000), 1).unwrap();
+            vm.write(VirtualAddress::from(0x0100), 1).unwrap();
+            let time = vm.tracker.simulated_time_ns;
+            vm.access(VirtualAddress::from(0x0200)).unwrap();
+            assert_eq!(vm.tracker.device_requests, 5);
+            assert_eq!(vm.tracker.simulated_time_ns - time, 3 * 6000 + 100);
+            assert_eq!(vm.tracker.device_wait_ns, 6000 + 12000 - 201);
+        }
+
+        #[test]
+        fn page_walk_cache() {
+            let mut config = standard_config("page_walk_cache");
+            config.page_walk_levels = 2;
+            config.size_pwc = 4;
+            let mut vm = standard_memory(&config);
+            vm.access(VirtualAddress::from(0x1200)).unwrap();
+            vm.access(VirtualAddress::from(0x1300)).unwrap();
+            vm.access(VirtualAddress::from(0x2300)).unwrap();
+            assert_eq!(vm.tracker.page_walks, 3);
+            assert_eq!(vm.tracker.pwc_hits, vec![1]);
+            assert_eq!(vm.tracker.walk_references, 5);
+            assert_eq!(vm.tracker.walk_references_saved, 1);
+        }
+
+        #[test]
+        fn write_back() {
+            let mut config = standard_config("write_back");
+            config.size_table = 2;
+            let mut vm = standard_memory(&config);
+            let result = vm.write(VirtualAddress::from(0x0010), 42).unwrap();
+            assert_eq!(result.value, 42);
+            assert!(vm.process().pages.find(0).unwrap().dirty);
+
+            vm.access(VirtualAddress::from(0x0100)).unwrap();
+            vm.access(VirtualAddress::from(0x0200)).unwrap();
+            assert_eq!(vm.tracker.page_outs, 1);
+            assert!(vm.process().pages.find(0).unwrap().swap_slot.is_some());
+
+            let result = vm.access(VirtualAddress::from(0x0010)).unwrap();
+            assert_eq!(result.value, 42);
+            assert_eq!(vm.tracker.swap_ins, 1);
+            assert_eq!(vm.tracker.write_accesses, 1);
+        }
+
+        #[test]
+        fn swap_space() {
+            let mut config = standard_config("swap_space");
+            config.size_table = 1;
+            config.size_swap = 1;
+            let mut vm = standard_memory(&config);
+            vm.write(VirtualAddress::from(0x0010), 42).unwrap();
+            vm.write(VirtualAddress::from(0x0110), 43).unwrap();
+            assert_eq!(vm.tracker.swap_slots_in_use, 1);
+            assert_eq!(vm.tracker.swap_writes, 1);
+            assert!(matches!(
+                vm.access(VirtualAddress::from(0x0200)),
+                Err(Error::OutOfMemory { pid: 0 })
+            ));
+            assert_eq!(vm.tracker.swap_slots_in_use, 0);
+            assert_eq!(vm.tracker.swap_slots_peak, 1);
+            assert_eq!(vm.tracker.swap_fragmentation(), 0.0);
+        }
+
+        #[test]
+        fn full_swap_space() {
+            let mut config = standard_config("full_swap_space");
+            config.size_table = 2;
+            config.size_swap = 1;
+            let mut vm = standard_memory(&config);
+            vm.write(VirtualAddress::from(0x0010), 42).unwrap();
+            vm.write(VirtualAddress::from(0x0110), 43).unwrap();
+            vm.access(VirtualAddress::from(0x0200)).unwrap();
+            assert_eq!(vm.tracker.swap_slots_in_use, 1);
+            vm.access(VirtualAddress::from(0x0300)).unwrap();
+            assert_eq!(vm.tracker.swap_writes, 1);
+            assert!(!vm.process().pages.find(2).unwrap().valid);
+            assert!(vm.process().pages.find(1).unwrap().valid);
+            assert_eq!(vm.access(VirtualAddress::from(0x0110)).unwrap().value, 43);
+            assert_eq!(vm.tracker.oom_kills, 0);
+        }
+
+        #[test]
+        fn compressed_swap() {
+            let mut config = standard_config("compressed_swap");
+            config.size_table = 1;
+            config.size_zswap = 8;
+            let contents = vec![0; SIZE_FRAME as usize * 4];
+            let mut vm = VirtualMemory::build(&config, MemoryStore::new(contents));
+            vm.write(VirtualAddress::from(0x0010), 42).unwrap();
+            vm.write(VirtualAddress::from(0x0110), 43).unwrap();
+            vm.access(VirtualAddress::from(0x0210)).unwrap();
+            assert_eq!(vm.tracker.zswap_writebacks, 1);
+            assert_eq!(vm.tracker.swap_writes, 1);
+
+            assert_eq!(vm.access(VirtualAddress::from(0x0010)).unwrap().value, 42);
+            assert_eq!(vm.access(VirtualAddress::from(0x0110)).unwrap().value, 43);
+            assert_eq!(vm.tracker.swap_ins, 1);
+            assert_eq!(vm.tracker.zswap_faults, 1);
+            assert_eq!(vm.tracker.zswap_hits, 1);
+            assert_eq!(vm.tracker.zswap_stores, 2);
+            assert_eq!(vm.tracker.zswap_original_bytes, 512);
+            assert_eq!(vm.tracker.zswap_compressed_bytes, 16);
+        }
+
+        #[test]
+        fn read_ahead() {
+            let mut config = standard_config("read_ahead");
+            config.size_table = 16;
+            config.prefetcher = String::from("readahead");
+            config.prefetch_window = 4;
+            let mut vm = standard_memory(&config);
+            vm.access(VirtualAddress::from(0x0000)).unwrap();
+            assert_eq!(vm.tracker.prefetches, 2);
+            vm.access(VirtualAddress::from(0x0100)).unwrap();
+            assert_eq!(vm.tracker.page_hits, 1);
+            assert_eq!(vm.tracker.prefetch_hits, 1);
+
+            vm.access(VirtualAddress::from(0x0300)).unwrap();
+            assert_eq!(vm.tracker.prefetches, 6);
+            let contents = std::fs::read(&config.file_storage).unwrap();
+            let result = vm.access(VirtualAddress::from(0x0514)).unwrap();
+            assert_eq!(result.value, contents[0x0514] as i8);
+            assert_eq!(vm.tracker.prefetch_hits, 2);
+
+            vm.unmap_area(0, 0, 8).unwrap();
+            assert_eq!(vm.tracker.prefetch_wasted, 4);
+        }
+
+        #[test]
+        fn stride_and_markov() {
+            let mut config = standard_config("stride_and_markov");
+            config.size_table = 16;
+            config.prefetch_window = 2;
+            config.prefetcher = String::from("stride");
+            let mut vm = standard_memory(&config);
+            for page_number in [0x10, 0x14, 0x18, 0x1C, 0x20] {
+                vm.access(VirtualAddress::from(page_number << 8)).unwrap();
+            }
+            assert_eq!(vm.tracker.page_faults, 3);
+            assert_eq!(vm.tracker.prefetch_hits, 2);
+            assert!(vm
+                .tracker
+                .to_string()
+                .contains("prefetcher:               stride"));
+
+            config.prefetcher = String::from("markov");
+            let mut vm = standard_memory(&config);
+            vm.access(VirtualAddress::from(0x3000)).unwrap();
+            vm.access(VirtualAddress::from(0x5000)).unwrap();
+            assert_eq!(vm.tracker.prefetches, 0);
+            let kind = AreaKind::File {
+                file: 0,
+                page_offset: 0,
+                shared: false,
+            };
+            vm.map_area(0, VirtualMemoryArea::new(0, 0x100, kind, Permissions::ALL))
+                .unwrap();
+            vm.access(VirtualAddress::from(0x3000)).unwrap();
+            vm.access(VirtualAddress::from(0x5000)).unwrap();
+            assert_eq!(vm.tracker.prefetches, 1);
+            assert_eq!(vm.tracker.prefetch_hits, 1);
+        }
+
+        #[test]
+        fn fault_around() {
+            let contents: Vec<u8> = (0..SIZE_FRAME * 6)
+                .map(|x| (x / SIZE_FRAME) as u8)
+                .collect();
+            let mut config = standard_config("fault_around");
+            config.fault_around = 4;
+            let mut vm = VirtualMemory::build(&config, MemoryStore::new(contents.clone()));
+            assert_eq!(vm.access(VirtualAddress::from(0x0100)).unwrap().value, 1);
+            assert_eq!(vm.tracker.fault_around_pages, 3);
+            assert_eq!(vm.access(VirtualAddress::from(0x0210)).unwrap().value, 2);
+            assert_eq!(vm.tracker.page_faults, 1);
+            assert_eq!(vm.tracker.fault_around_io_saved, 1);
+
+            assert_eq!(vm.access(VirtualAddress::from(0x0500)).unwrap().value, 5);
+            assert_eq!(vm.tracker.fault_around_pages, 4);
+            assert_eq!(vm.access(VirtualAddress::from(0x0400)).unwrap().value, 4);
+            assert_eq!(vm.tracker.fault_around_io_saved, 2);
+
+            config.size_table = 2;
+            let mut vm = VirtualMemory::build(&config, MemoryStore::new(contents));
+            vm.access(VirtualAddress::from(0x0000)).unwrap();
+            assert_eq!(vm.tracker.fault_around_pages, 1);
+        }
+
+        #[test]
+        fn reclaim() {
+            let mut config = standard_config("reclaim");
+            config.size_table = 8;
+            config.watermark_min = 1;
+            config.watermark_low = 2;
+            config.watermark_high = 4;
+            config.reclaim_interval = 4;
+            let mut vm = standard_memory(&config);
+            for page_number in 0..7 {
+                vm.access(VirtualAddress::from(page_number << 8)).unwrap();
+            }
+            assert_eq!(vm.frames.free.len(), 1);
+            assert_eq!(vm.tracker.direct_reclaims, 0);
+
+            vm.access(VirtualAddress::from(0x0700)).unwrap();
+            assert_eq!(vm.tracker.direct_reclaims, 1);
+            assert_eq!(vm.tracker.background_reclaims, 3);
+            assert_eq!(vm.frames.free.len(), 4);
+            vm.access(VirtualAddress::from(0x0100)).unwrap();
+            assert_eq!(vm.tracker.page_faults, 9);
+        }
+
+        #[test]
+        fn page_buffering() {
+            let mut config = standard_config("page_buffering");
+            config.size_table = 4;
+            config.watermark_min = 2;
+            config.watermark_low = 2;
+            config.watermark_high = 2;
+            config.size_modified_list = 1;
+            let mut vm = standard_memory(&config);
+            vm.access(VirtualAddress::from(0x0000)).unwrap();
+            vm.write(VirtualAddress::from(0x0105), 42).unwrap();
+            vm.access(VirtualAddress::from(0x0200)).unwrap();
+            vm.access(VirtualAddress::from(0x0300)).unwrap();
+            assert_eq!(vm.frames.modified.len(), 1);
+            assert_eq!(vm.tracker.hard_faults, 4);
+
+            assert_eq!(vm.access(VirtualAddress::from(0x0105)).unwrap().value, 42);
+            vm.access(VirtualAddress::from(0x0000)).unwrap();
+            assert_eq!(vm.tracker.soft_faults, 2);
+            assert_eq!(vm.tracker.hard_faults, 4);
+            assert_eq!(vm.tracker.page_outs, 0);
+
+            vm.access(VirtualAddress::from(0x0400)).unwrap();
+            assert_eq!(vm.tracker.direct_reclaims, 6);
+            assert_eq!(vm.frames.modified.len(), 1);
+            let child = vm.fork(0).unwrap();
+            assert_eq!(vm.tracker.page_outs, 1);
+            vm.switch_process(child).unwrap();
+            assert_eq!(vm.access(VirtualAddress::from(0x0105)).unwrap().value, 42);
+            assert_eq!(vm.tracker.swap_ins, 1);
+        }
+
+        #[test]
+        fn huge_pages() {
+            let mut vm = standard_memory(&standard_config("huge_pages"));
+            let area = VirtualMemoryArea {
+                page_size: 16,
+                ..VirtualMemoryArea::new(0x40, 32, AreaKind::Anonymous, Permissions::READ_WRITE)
+            };
+            vm.map_area(0, area.clone()).unwrap();
+            vm.write(VirtualAddress::from(0x4105), 7).unwrap();
+            assert_eq!(vm.tracker.page_faults, 1);
+            assert_eq!(vm.tracker.zero_fill_faults, 16);
+            let result = vm.access(VirtualAddress::from(0x4F00)).unwrap();
+            assert_eq!(result.physical_address, 0x0F00);
+            assert_eq!(vm.tracker.tlb_hits_by_size[&16], 1);
+            assert_eq!(vm.tracker.tlb_reach_by_size[&16], 16 * SIZE_FRAME as usize);
+            assert_eq!(vm.tracker.huge_fragmentation, 14);
+
+            let child = vm.fork(0).unwrap();
+            vm.switch_process(child).unwrap();
+            vm.write(VirtualAddress::from(0x4200), 1).unwrap();
+            assert_eq!(vm.access(VirtualAddress::from(0x4105)).unwrap().value, 7);
+            assert_eq!(vm.tracker.cow_copies, 1);
+            assert_eq!(vm.tracker.tlb_hits_by_size[&1], 0);
+            vm.switch_process(0).unwrap();
+            assert_eq!(vm.access(VirtualAddress::from(0x4200)).unwrap().value, 0);
+            assert_eq!(vm.tracker.tlb_hits_by_size[&16], 2);
+
+            for (start, kind) in [(0x48, AreaKind::Anonymous), (0x80, AreaKind::Stack)] {
+                let area = VirtualMemoryArea {
+                    page_size: 16,
+                    ..VirtualMemoryArea::new(start, 16, kind, Permissions::READ_WRITE)
+                };
+                assert!(matches!(
+                    vm.map_area(0, area),
+                    Err(Error::InvalidPageSize { page_size: 16, .. })
+                ));
+            }
+
+            let kind = AreaKind::File {
+                file: 0,
+                page_offset: 0,
+                shared: false,
+            };
+            let area = VirtualMemoryArea {
+                page_size: 16,
+                ..VirtualMemoryArea::new(0xA0, 32, kind, Permissions::ALL)
+            };
+            vm.map_area(0, area).unwrap();
+            let heap = VirtualMemoryArea::new(0xA0, 4, AreaKind::Heap, Permissions::READ_WRITE);
+            vm.map_area(0, heap).unwrap();
+            vm.access(VirtualAddress::from(0xA400)).unwrap();
+            assert!(vm.process().pages.find(0xA0).is_none());
+            assert_eq!(vm.access(VirtualAddress::from(0xA00F)).unwrap().value, 0);
+        }
+
+        #[test]
+        fn transparent_huge_pages() {
+            let mut config = standard_config("transparent_huge_pages");
+            config.size_table = 8;
+            config.size_thp = 4;
+            config.thp_scan_interval = 4;
+            let mut vm = standard_memory(&config);
+            let contents = std::fs::read(&config.file_storage).unwrap();
+            for page_number in 0x10..0x14 {
+                vm.access(VirtualAddress::from(page_number << 8)).unwrap();
+            }
+            assert_eq!(vm.tracker.thp_promotions, 1);
+            let result = vm.access(VirtualAddress::from(0x1107)).unwrap();
+            assert_eq!(result.value, contents[0x1107] as i8);
+            vm.access(VirtualAddress::from(0x1300)).unwrap();
+            assert_eq!(vm.tracker.tlb_hits_by_size[&4], 1);
+
+            for page_number in 0x20..0x23 {
+                vm.access(VirtualAddress::from(page_number << 8)).unwrap();
+            }
+            assert_eq!(vm.tracker.thp_promotions, 1);
+            for address in [0x2300, 0x2100, 0x2200, 0x3000] {
+                vm.access(VirtualAddress::from(address)).unwrap();
+            }
+            assert_eq!(vm.tracker.thp_promotions, 2);
+            assert_eq!(vm.tracker.thp_demotions, 1);
+            let result = vm.access(VirtualAddress::from(0x1207)).unwrap();
+            assert_eq!(result.value, contents[0x1207] as i8);
+            assert_eq!(vm.tracker.page_faults, 9);
+        }
+
+        #[test]
+        fn failed_promotion() {
+            let mut config = standard_config("failed_promotion");
+            config.size_table = 8;
+            config.size_thp = 4;
+            config.thp_scan_interval = 6;
+            config.max_pinned_frames = 2;
+            let mut vm = standard_memory(&config);
+            vm.pin(0x20).unwrap();
+            for page_number in 0..4 {
+                vm.write(VirtualAddress::from(page_number << 8), page_number as i8)
+                    .unwrap();
+            }
+            vm.pin(0x21).unwrap();
+            vm.access(VirtualAddress::from(0x0010)).unwrap();
+            vm.access(VirtualAddress::from(0x0110)).unwrap();
+            assert_eq!(vm.tracker.thp_promotions, 0);
+            assert_eq!(vm.frames.free.len(), 2);
+            for page_number in 0..4 {
+                let page = vm.process().pages.find(page_number).unwrap();
+                assert_eq!(page.huge, None);
+                let frame = &vm.frames.entries[page.frame_index];
+                assert_eq!(frame.mappings, vec![(0, page_number)]);
+            }
+
+            vm.access(VirtualAddress::from(0x3000)).unwrap();
+            vm.access(VirtualAddress::from(0x3100)).unwrap();
+            for page_number in 0..4 {
+                let result = vm.access(VirtualAddress::from(page_number << 8)).unwrap();
+                assert_eq!(result.value, page_number as i8);
+            }
+        }
+
+        #[test]
+        fn pinning() {
+            let mut config = standard_config("pinning");
+            config.size_table = 4;
+            config.max_pinned_frames = 3;
+            let mut vm = standard_memory(&config);
+            for page_number in [0, 1, 2, 1] {
+                vm.pin(page_number).unwrap();
+            }
+            assert!(matches!(
+                vm.pin(3),
+                Err(Error::PinLimitExceeded { limit: 3 })
+            ));
+            assert_eq!(vm.tracker.pinned_frames, 3);
+            for address in [0x0400, 0x0500, 0x0600, 0x0700, 0x0000] {
+                vm.access(VirtualAddress::from(address)).unwrap();
+            }
+            assert_eq!(vm.tracker.page_faults, 8);
+
+            vm.max_pinned_frames = 4;
+            vm.pin(7).unwrap();
+            vm.unpin(0);
+            vm.access(VirtualAddress::from(0x0300)).unwrap();
+            vm.access(VirtualAddress::from(0x0000)).unwrap();
+            assert_eq!(vm.tracker.page_faults, 10);
+            assert_eq!(vm.tracker.pinned_frames, 3);
+        }
+
+        #[test]
+        fn oom_kill() {
+            let mut config = standard_config("oom_kill");
+            config.size_table = 4;
+            config.max_pinned_frames = 4;
+            let mut vm = standard_memory(&config);
+            vm.write(VirtualAddress::from(0x0010), 42).unwrap();
+            let child = vm.fork(0).unwrap();
+            vm.switch_process(child).unwrap();
+            for page_number in [0, 1, 2, 3] {
+                vm.pin(page_number).unwrap();
+            }
+
+            vm.switch_process(0).unwrap();
+            vm.access(VirtualAddress::from(0x0500)).unwrap();
+            assert_eq!(
+                vm.events,
+                vec![Event::OomKill {
+                    pid: child,
+                    resident_pages: 4
+                }]
+            );
+            assert_eq!(vm.tracker.oom_kills, 1);
+            assert_eq!(vm.tracker.pinned_frames, 0);
+            assert!(matches!(
+                vm.switch_process(child),
+                Err(Error::UnknownProcess(1))
+            ));
+            assert_eq!(vm.access(VirtualAddress::from(0x0010)).unwrap().value, 42);
+
+            for page_number in [0, 5, 6, 7] {
+                vm.pin(page_number).unwrap();
+            }
+            assert!(matches!(
+                vm.access(VirtualAddress::from(0x0800)),
+                Err(Error::OutOfMemory { pid: 0 })
+            ));
+            assert_eq!(vm.tracker.oom_kills, 2);
+            assert_eq!(vm.frames.free.len(), 4);
+
+            let page_faults = vm.tracker.page_faults;
+            for address in [0x0010, 0x0800] {
+                assert!(matches!(
+                    vm.access(VirtualAddress::from(address)),
+                    Err(Error::UnknownProcess(0))
+                ));
+            }
+            assert!(matches!(vm.pin(0), Err(Error::UnknownProcess(0))));
+            vm.unpin(0);
+            vm.protect(0, Permissions::READ_WRITE);
+            assert_eq!(vm.tracker.page_faults, page_faults);
+        }
+
+        #[test]
+        fn oom_kill_spares_clean_frames() {
+            let mut config = standard_config("oom_kill_spares_clean_frames");
+            config.size_table = 4;
+            config.size_swap = 1;
+            let mut vm = standard_memory(&config);
+            vm.write(VirtualAddress::from(0x0010), 42).unwrap();
+            vm.write(VirtualAddress::from(0x0110), 43).unwrap();
+            for address in [0x0200, 0x0300, 0x0400, 0x0500] {
+                vm.access(VirtualAddress::from(address)).unwrap();
+            }
+            assert_eq!(vm.tracker.oom_kills, 0);
+            assert!(vm.process().pages.find(1).unwrap().valid);
+            assert!(!vm.process().pages.find(2).unwrap().valid);
+            assert_eq!(vm.access(VirtualAddress::from(0x0010)).unwrap().value, 42);
+            assert_eq!(vm.access(VirtualAddress::from(0x0110)).unwrap().value, 43);
+        }
+
+        #[test]
+        fn same_page_merging() {
+            let mut config = standard_config("same_page_merging");
+            config.size_table = 8;
+            config.ksm_scan_interval = 4;
+            let contents = vec![0; SIZE_FRAME as usize * 8];
+            let mut vm = VirtualMemory::build(&config, MemoryStore::new(contents));
+            for address in [0x0000, 0x0100, 0x0200, 0x0300] {
+                vm.access(VirtualAddress::from(address)).unwrap();
+            }
+            assert_eq!(vm.tracker.ksm_merges, 3);
+            assert_eq!(vm.tracker.ksm_frames_saved, 3);
+            assert_eq!(vm.frames.free.len(), 7);
+            assert_eq!(vm.frames.entries[0].ref_count(), 4);
+            let merged = vm.access(VirtualAddress::from(0x0210)).unwrap();
+            assert_eq!(merged.physical_address, 0x0010);
+
+            let copied = vm.write(VirtualAddress::from(0x0110), 7).unwrap();
+            assert_ne!(copied.physical_address, 0x0110);
+            assert_eq!(vm.tracker.ksm_unmerges, 1);
+            assert_eq!(vm.tracker.ksm_frames_saved, 2);
+            assert_eq!(vm.access(VirtualAddress::from(0x0110)).unwrap().value, 7);
+            assert_eq!(vm.access(VirtualAddress::from(0x0010)).unwrap().value, 0);
+            assert_eq!(vm.tracker.ksm_merges, 3);
+        }
+
+        #[test]
+        fn protection_fault() {
+            let mut vm = standard_memory(&standard_config("protection_fault"));
+            vm.protect(1, Permissions::READ_ONLY);
+            assert!(vm.access(VirtualAddress::from(0x0100)).is_ok());
+            assert!(matches!(
+                vm.write(VirtualAddress::from(0x0100), 1),
+                Err(Error::ProtectionFault {
+                    page_number: 1,
+                    kind: AccessKind::Write(1)
+                })
+            ));
+            assert_eq!(
+                vm.process().pages.find(1).unwrap().permissions,
+                Permissions::READ_ONLY
+            );
+
+            vm.protect(2, Permissions::READ_WRITE);
+            let execute = MemoryAccess {
+                virtual_address: VirtualAddress::from(0x0200),
+                kind: AccessKind::Execute,
+            };
+            assert!(vm.perform(execute).is_err());
+            assert_eq!(vm.tracker.write_protection_faults, 1);
+            assert_eq!(vm.tracker.execute_protection_faults, 1);
+            assert_eq!(vm.tracker.read_protection_faults, 0);
+        }
+
+        #[test]
+        fn fork() {
+            let mut vm = standard_memory(&standard_config("fork"));
+            vm.write(VirtualAddress::from(0x0010), 42).unwrap();
+            vm.access(VirtualAddress::from(0x0100)).unwrap();
+            let child = vm.fork(0).unwrap();
+            assert_eq!(child, 1);
+            assert_eq!(vm.tracker.cow_frames_shared, 2);
+            assert!(vm.process().pages.find(0).unwrap().cow);
+            assert_eq!(vm.frames.entries[0].ref_count(), 2);
+
+            vm.switch_process(child).unwrap();
+            let shared = vm.access(VirtualAddress::from(0x0010)).unwrap();
+            assert_eq!(shared.value, 42);
+            assert_eq!(shared.physical_address, 0x0010);
+
+            let copied = vm.write(VirtualAddress::from(0x0010), 7).unwrap();
+            assert_ne!(copied.physical_address, shared.physical_address);
+            assert_eq!(vm.tracker.cow_copies, 1);
+            assert_eq!(vm.frames.entries[0].ref_count(), 1);
+
+            vm.switch_process(0).unwrap();
+            assert_eq!(vm.access(VirtualAddress::from(0x0010)).unwrap().value, 42);
+            vm.write(VirtualAddress::from(0x0010), 9).unwrap();
+            assert_eq!(vm.tracker.cow_copies, 1);
+            assert!(!vm.process().pages.find(0).unwrap().cow);
+            assert!(matches!(vm.fork(5), Err(Error::UnknownProcess(5))));
+            assert!(vm.switch_process(5).is_err());
+        }
+
+        #[test]
+        fn fork_with_eviction() {
+            let mut config = standard_config("fork_with_eviction");
+            config.size_table = 2;
+            let mut vm = standard_memory(&config);
+            vm.write(VirtualAddress::from(0x0010), 42).unwrap();
+            let child = vm.fork(0).unwrap();
+            vm.access(VirtualAddress::from(0x0100)).unwrap();
+            vm.access(VirtualAddress::from(0x0200)).unwrap();
+            assert!(!vm.process().pages.find(0).unwrap().valid);
+            assert_eq!(vm.tracker.page_outs, 2);
+
+            vm.switch_process(child).unwrap();
+            assert_eq!(vm.access(VirtualAddress::from(0x0010)).unwrap().value, 42);
+            let grandchild = vm.fork(child).unwrap();
+            vm.access(VirtualAddress::from(0x0100)).unwrap();
+            vm.access(VirtualAddress::from(0x0200)).unwrap();
+            vm.switch_process(grandchild).unwrap();
+            assert_eq!(vm.access(VirtualAddress::from(0x0010)).unwrap().value, 42);
+        }
+
+        #[test]
+        fn failed_fork() {
+            let mut config = standard_config("failed_fork");
+            config.size_table = 2;
+            config.size_swap = 3;
+            let mut vm = standard_memory(&config);
+            vm.write(VirtualAddress::from(0x0010), 42).unwrap();
+            vm.write(VirtualAddress::from(0x0110), 43).unwrap();
+            vm.write(VirtualAddress::from(0x0210), 44).unwrap();
+            vm.access(VirtualAddress::from(0x0300)).unwrap();
+            assert_eq!(vm.swap.available(), 1);
+
+            let pages = vm.process().pages.0.clone();
+            let mappings: Vec<_> = vm
+                .frames
+                .entries
+                .iter()
+                .map(|f| f.mappings.clone())
+                .collect();
+            assert!(matches!(vm.fork(0), Err(Error::SwapExhausted)));
+            assert_eq!(vm.swap.available(), 1);
+            assert_eq!(vm.process().pages.0, pages);
+            let after: Vec<_> = vm
+                .frames
+                .entries
+                .iter()
+                .map(|f| f.mappings.clone())
+                .collect();
+            assert_eq!(after, mappings);
+            assert_eq!(vm.tracker.cow_frames_shared, 0);
+            assert_eq!(vm.tracker.forks, 0);
+            assert!(vm.switch_process(1).is_err());
+        }
+
+        #[test]
+        fn copy_on_write_with_eviction() {
+            let mut config = standard_config("copy_on_write_with_eviction");
+            config.size_table = 2;
+            let mut vm = standard_memory(&config);
+            vm.write(VirtualAddress::from(0x0010), 42).unwrap();
+            vm.access(VirtualAddress::from(0x0100)).unwrap();
+            let child = vm.fork(0).unwrap();
+            vm.switch_process(child).unwrap();
+            let copied = vm.write(VirtualAddress::from(0x0010), 7).unwrap();
+            assert_eq!(copied.physical_address, 0x0110);
+            assert!(!vm.process().pages.find(0).unwrap().cow);
+            vm.access(VirtualAddress::from(0x0200)).unwrap();
+            vm.access(VirtualAddress::from(0x0300)).unwrap();
+            assert_eq!(vm.access(VirtualAddress::from(0x0010)).unwrap().value, 7);
+
+            vm.switch_process(0).unwrap();
+            assert_eq!(vm.access(VirtualAddress::from(0x0010)).unwrap().value, 42);
+        }
+
+        #[test]
+        fn virtual_memory_areas() {
+            let mut vm = standard_memory(&standard_config("virtual_memory_areas"));
+            vm.access(VirtualAddress::from(0x8000)).unwrap();
+            vm.unmap_area(0, 0x80, 0x80).unwrap();
+            assert!(matches!(
+                vm.access(VirtualAddress::from(0x8000)),
+                Err(Error::SegmentationFault { page_number: 0x80 })
+            ));
+            assert_eq!(vm.frames.entries[0].ref_count(), 0);
+
+            let file = AreaKind::File {
+                file: 0,
+                page_offset: 0x75,
+                shared: false,
+            };
+            vm.map_area(0, VirtualMemoryArea::new(0xA0, 1, file, Permissions::ALL))
+                .unwrap();
+            assert_eq!(vm.access(VirtualAddress::from(0xA0F6)).unwrap().value, 29);
+
+            let heap = VirtualMemoryArea::new(0x40, 2, AreaKind::Heap, Permissions::READ_ONLY);
+            vm.map_area(0, heap).unwrap();
+            assert_eq!(vm.access(VirtualAddress::from(0x4010)).unwrap().value, 0);
+            assert_eq!(vm.tracker.zero_fill_faults, 1);
+            assert!(matches!(
+                vm.write(VirtualAddress::from(0x4110), 1),
+                Err(Error::ProtectionFault { .. })
+            ));
+
+            let stack = VirtualMemoryArea::new(0x90, 1, AreaKind::Stack, Permissions::READ_WRITE);
+            vm.map_area(0, stack).unwrap();
+            vm.write(VirtualAddress::from(0x8F00), 5).unwrap();
+            assert_eq!(vm.tracker.stack_growths, 1);
+            assert!(vm.access(VirtualAddress::from(0x8D00)).is_err());
+            assert_eq!(vm.tracker.segmentation_faults, 2);
+        }
+
+        #[test]
+        fn shared_segment() {
+            let mut config = standard_config("shared_segment");
+            config.size_table = 4;
+            let mut vm = standard_memory(&config);
+            let other = vm.fork(0).unwrap();
+            let segment = vm.create_segment(2);
+            vm.attach_segment(0, segment, 0x10, Permissions::READ_WRITE)
+                .unwrap();
+            vm.attach_segment(other, segment, 0x20, Permissions::READ_WRITE)
+                .unwrap();
+
+            let written = vm.write(VirtualAddress::from(0x1004), 42).unwrap();
+            vm.switch_process(other).unwrap();
+            let read = vm.access(VirtualAddress::from(0x2004)).unwrap();
+            assert_eq!(read.value, 42);
+            assert_eq!(read.physical_address, written.physical_address);
+            assert_eq!(vm.tracker.minor_faults, 1);
+            assert_eq!(vm.access(VirtualAddress::from(0x2104)).unwrap().value, 0);
+
+            (1..5).for_each(|page| {
+                vm.access(VirtualAddress::from(page << 8)).unwrap();
             });
+            assert_eq!(vm.tracker.rmap_invalidations, 3);
+            assert_eq!(vm.tracker.page_outs, 1);
+            assert!(vm.tlb.find((0, 0x10)).is_none());
+            assert!(vm.tlb.find((other, 0x20)).is_none());
+
+            vm.switch_process(0).unwrap();
+            assert_eq!(vm.access(VirtualAddress::from(0x1004)).unwrap().value, 42);
+            assert_eq!(vm.tracker.swap_ins, 1);
+            assert!(matches!(
+                vm.attach_segment(0, 99, 0, Permissions::ALL),
+                Err(Error::UnknownSegment(99))
+            ));
+        }
+
+        #[test]
+        fn file_mappings() {
+            let mut vm = standard_memory(&standard_config("file_mappings"));
+            let filename = std::env::temp_dir().join("virtual_memory_tests_file_mappings_data.bin");
+            let contents: Vec<u8> = (0..2 * SIZE_FRAME)
+                .map(|x| (x / SIZE_FRAME + 1) as u8)
+                .collect();
+            std::fs::write(&filename, &contents).unwrap();
+            let file = vm.open_file(filename.to_str().unwrap(), true);
+            assert_eq!(file, 1);
+
+            let kind = |shared| AreaKind::File {
+                file,
+                page_offset: 0,
+                shared,
+            };
+            let shared = VirtualMemoryArea::new(0x10, 2, kind(true), Permissions::READ_WRITE);
+            let private = VirtualMemoryArea::new(0x20, 2, kind(false), Permissions::READ_WRITE);
+            vm.map_area(0, shared).unwrap();
+            vm.map_area(0, private).unwrap();
+            let child = vm.fork(0).unwrap();
+
+            vm.switch_process(child).unwrap();
+            vm.write(VirtualAddress::from(0x1000), 9).unwrap();
+            vm.write(VirtualAddress::from(0x2101), 5).unwrap();
+            vm.switch_process(0).unwrap();
+            assert_eq!(vm.access(VirtualAddress::from(0x1000)).unwrap().value, 9);
+            assert_eq!(vm.access(VirtualAddress::from(0x2101)).unwrap().value, 2);
+            assert_eq!(vm.tracker.minor_faults, 1);
 
-            assert!(tlb.find(max).is_none());
+            vm.unmap_area(child, 0x10, 2).unwrap();
+            vm.unmap_area(0, 0x10, 2).unwrap();
+            assert_eq!(vm.tracker.file_write_backs, 1);
+            let written = std::fs::read(&filename).unwrap();
+            assert_eq!(written[0], 9);
+            assert_eq!(written[SIZE_FRAME as usize + 1], 2);
+            assert!(matches!(
+                vm.map_area(
+                    0,
+                    VirtualMemoryArea::new(
+                        0,
+                        1,
+                        AreaKind::File {
+                            file: 7,
+                            page_offset: 0,
+                            shared: false
+                        },
+                        Permissions::ALL
+                    )
+                ),
+                Err(Error::UnknownFile(7))
+            ));
         }
     }
 }
@@ -0,0 +1,294 @@
+use crate::virtual_memory::Permissions;
+
+/// `AreaKind` describes where the contents of a virtual memory area originate. File areas are read
+/// from one of the open files (the backing store is file zero) beginning at `page_offset`, while
+/// anonymous, stack, and heap areas are zero-filled on first reference. Shared areas map the pages
+/// of a shared memory segment, again beginning at `page_offset`.
+///
+/// As with `MAP_PRIVATE`, writes to a private file area are seen only by the writing process and
+/// never reach the file. A `shared` file area behaves like `MAP_SHARED` instead: every process
+/// mapping the same page of the file maps the same frame, and modified pages are written back to
+/// the file itself.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AreaKind {
+    File {
+        file: usize,
+        page_offset: usize,
+        shared: bool,
+    },
+    Anonymous,
+    Stack,
+    Heap,
+    Shared {
+        segment: usize,
+        page_offset: usize,
+    },
+}
+
+/// The `VirtualMemoryArea` struct describes a contiguous range of logical pages within a single
+/// address space which share a common origin and set of permissions. Accesses to pages outside
+/// every area of a process are segmentation faults.
+///
+/// Areas are mapped with base pages unless `page_size` is set to the number of base pages making
+/// up each of its (huge) pages, in which case the start and length of the area must be multiples
+/// of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirtualMemoryArea {
+    pub start: usize,
+    pub length: usize,
+    pub kind: AreaKind,
+    pub permissions: Permissions,
+    pub page_size: usize,
+}
+
+impl VirtualMemoryArea {
+    /// Create a new `VirtualMemoryArea` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - the first logical page number of the area.
+    /// * `length` - the number of pages in the area.
+    /// * `kind` - the origin of the area contents.
+    /// * `permissions` - the kinds of access permitted on pages of the area.
+    pub fn new(start: usize, length: usize, kind: AreaKind, permissions: Permissions) -> Self {
+        Self {
+            start,
+            length,
+            kind,
+            permissions,
+            page_size: 1,
+        }
+    }
+
+    /// Return the logical page number immediately following the area.
+    pub fn end(&self) -> usize {
+        self.start + self.length
+    }
+
+    /// Determine whether the provided logical page number lies within the area.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number
+    pub fn contains(&self, page_number: usize) -> bool {
+        (self.start..self.end()).contains(&page_number)
+    }
+
+    /// Provided a logical page number within the area, return the page offset used to locate its
+    /// contents within the backing store or shared memory segment. Zero-filled areas have no such
+    /// offset and return `None`.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number
+    pub fn source_page(&self, page_number: usize) -> Option<usize> {
+        match self.kind {
+            AreaKind::File { page_offset, .. } | AreaKind::Shared { page_offset, .. } => {
+                Some(page_offset + page_number - self.start)
+            }
+            _ => None,
+        }
+    }
+
+    /// Return the portion of the area spanning the logical pages `start..end`, adjusting the
+    /// offset of file and shared areas so each page keeps its original source. Should the portion
+    /// no longer begin and end on a huge page boundary, it is mapped with base pages instead.
+    fn slice(&self, start: usize, end: usize) -> Self {
+        let shift = start - self.start;
+        let kind = match self.kind {
+            AreaKind::File {
+                file,
+                page_offset,
+                shared,
+            } => AreaKind::File {
+                file,
+                page_offset: page_offset + shift,
+                shared,
+            },
+            AreaKind::Shared {
+                segment,
+                page_offset,
+            } => AreaKind::Shared {
+                segment,
+                page_offset: page_offset + shift,
+            },
+            kind => kind,
+        };
+        let aligned = start.is_multiple_of(self.page_size) && end.is_multiple_of(self.page_size);
+        Self {
+            page_size: if aligned { self.page_size } else { 1 },
+            ..Self::new(start, end - start, kind, self.permissions)
+        }
+    }
+}
+
+/// The `AreaMap` struct holds the virtual memory areas of a single address space ordered by their
+/// starting page. Areas never overlap; mapping a new area over existing ones replaces the
+/// overlapping portions in the manner of `mmap` with `MAP_FIXED`.
+#[derive(Debug, Clone, Default)]
+pub struct AreaMap(Vec<VirtualMemoryArea>);
+
+impl AreaMap {
+    /// Create a new `AreaMap` instance containing no areas.
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Provided a logical page number, return the area containing it (if any).
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number
+    pub fn find(&self, page_number: usize) -> Option<&VirtualMemoryArea> {
+        self.0.iter().find(|area| area.contains(page_number))
+    }
+
+    /// The behavior of `find_mut` is identical to that of the `find` method with the only
+    /// exception being that the `Some` variant contains a mutable.
+    ///
+    /// # Arguments
+    ///
+    /// * `page_number` - logical page number
+    pub fn find_mut(&mut self, page_number: usize) -> Option<&mut VirtualMemoryArea> {
+        self.0.iter_mut().find(|area| area.contains(page_number))
+    }
+
+    /// Add an area to the map, first removing any portion of existing areas it overlaps.
+    ///
+    /// # Arguments
+    ///
+    /// * `area` - the area to be mapped.
+    pub fn insert(&mut self, area: VirtualMemoryArea) {
+        self.remove(area.start, area.length);
+        let index = self.0.partition_point(|other| other.start < area.start);
+        self.0.insert(index, area);
+    }
+
+    /// Remove the logical pages `start..start + length` from the map. Areas partially covered by
+    /// the range are trimmed or split in two.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - the first logical page number to remove.
+    /// * `length` - the number of pages to remove.
+    pub fn remove(&mut self, start: usize, length: usize) {
+        let end = start + length;
+        self.0 = self
+            .0
+            .iter()
+            .flat_map(|area| {
+                if area.end() <= start || area.start >= end {
+                    return vec![area.clone()];
+                }
+                let mut pieces = Vec::new();
+                if area.start < start {
+                    pieces.push(area.slice(area.start, start));
+                }
+                if area.end() > end {
+                    pieces.push(area.slice(end, area.end()));
+                }
+                pieces
+            })
+            .collect();
+    }
+
+    /// Return an iterator over the areas of the map in order of their starting page.
+    pub fn iter(&self) -> impl Iterator<Item = &VirtualMemoryArea> {
+        self.0.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[cfg(test)]
+    mod virtual_memory_area_tests {
+
+        use super::*;
+
+        #[test]
+        fn source_page() {
+            let kind = AreaKind::File {
+                file: 1,
+                page_offset: 10,
+                shared: false,
+            };
+            let file = VirtualMemoryArea::new(4, 4, kind, Permissions::ALL);
+            assert!(file.contains(7));
+            assert!(!file.contains(8));
+            assert_eq!(file.source_page(6), Some(12));
+
+            let stack = VirtualMemoryArea::new(4, 4, AreaKind::Stack, Permissions::READ_WRITE);
+            assert_eq!(stack.source_page(6), None);
+        }
+    }
+
+    #[cfg(test)]
+    mod area_map_tests {
+
+        use super::*;
+
+        fn make_standard_map() -> AreaMap {
+            let mut map = AreaMap::new();
+            map.insert(VirtualMemoryArea::new(
+                0,
+                16,
+                AreaKind::File {
+                    file: 0,
+                    page_offset: 0,
+                    shared: false,
+                },
+                Permissions::READ_EXECUTE,
+            ));
+            map
+        }
+
+        #[test]
+        fn find() {
+            let map = make_standard_map();
+            assert_eq!(map.find(15).unwrap().start, 0);
+            assert!(map.find(16).is_none());
+        }
+
+        #[test]
+        fn insert_splits_overlap() {
+            let mut map = make_standard_map();
+            map.insert(VirtualMemoryArea::new(
+                4,
+                2,
+                AreaKind::Heap,
+                Permissions::READ_WRITE,
+            ));
+            let areas: Vec<(usize, usize)> =
+                map.iter().map(|area| (area.start, area.length)).collect();
+            assert_eq!(areas, vec![(0, 4), (4, 2), (6, 10)]);
+            assert_eq!(map.find(4).unwrap().kind, AreaKind::Heap);
+            assert_eq!(map.find(6).unwrap().source_page(6), Some(6));
+        }
+
+        #[test]
+        fn remove() {
+            let mut map = make_standard_map();
+            map.remove(0, 8);
+            assert!(map.find(7).is_none());
+            assert_eq!(map.find(8).unwrap().source_page(8), Some(8));
+        }
+
+        #[test]
+        fn remove_unaligned_huge() {
+            let mut map = AreaMap::new();
+            map.insert(VirtualMemoryArea {
+                page_size: 4,
+                ..VirtualMemoryArea::new(0, 16, AreaKind::Anonymous, Permissions::ALL)
+            });
+            map.remove(4, 2);
+            let sizes: Vec<(usize, usize)> = map
+                .iter()
+                .map(|area| (area.start, area.page_size))
+                .collect();
+            assert_eq!(sizes, vec![(0, 4), (6, 1)]);
+        }
+    }
+}
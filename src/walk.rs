@@ -0,0 +1,237 @@
+use crate::virtual_memory::Pid;
+use crate::MASK_PAGE;
+use linked_hash_map::LinkedHashMap;
+
+/// The `WalkResult` struct describes the cost of a single simulated page table walk. Where the
+/// walk began is recorded as the deepest paging-structure level found in the cache (if any), while
+/// `references` counts the memory loads needed to finish resolving the page table entry.
+#[derive(Debug, PartialEq)]
+pub struct WalkResult {
+    pub cached_level: Option<usize>,
+    pub references: usize,
+}
+
+/// The `PagingStructureCache` struct is a virtualization of the MMU caches found in CPUs which
+/// store upper-level page table entries. Each element is keyed by the owning process and level of
+/// the entry along with the prefix of the page number used to index every level up to and
+/// including that one. Elements are victimized in least recently used order regardless of their
+/// level.
+struct PagingStructureCache {
+    table_size: usize,
+    map: LinkedHashMap<(Pid, usize, usize), ()>,
+}
+
+impl PagingStructureCache {
+    /// Create and return a new `PagingStructureCache` instance using the provided cache size.
+    ///
+    /// # Arguments
+    ///
+    /// * `table_size` - the maximum number of upper-level entries held across all levels.
+    fn build(table_size: usize) -> Self {
+        Self {
+            table_size,
+            map: LinkedHashMap::with_capacity(table_size),
+        }
+    }
+
+    /// Search the cache for the entry found at `level` and indexed by `prefix`. A hit refreshes
+    /// the position of the entry within the victimization queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `pid` - the process owning the page table.
+    /// * `level` - the page table level of the entry (the root is level zero).
+    /// * `prefix` - the page number bits used to index levels zero through `level`.
+    fn find(&mut self, pid: Pid, level: usize, prefix: usize) -> bool {
+        self.map.get_refresh(&(pid, level, prefix)).is_some()
+    }
+
+    /// Cache an upper-level entry, victimizing the least recently used element when full.
+    ///
+    /// # Arguments
+    ///
+    /// * `pid` - the process owning the page table.
+    /// * `level` - the page table level of the entry (the root is level zero).
+    /// * `prefix` - the page number bits used to index levels zero through `level`.
+    fn cache_element(&mut self, pid: Pid, level: usize, prefix: usize) {
+        let key = (pid, level, prefix);
+        if self.map.remove(&key).is_none() && self.map.len() == self.table_size {
+            self.map.pop_front();
+        }
+        self.map.insert(key, ());
+    }
+}
+
+/// The `PageWalker` struct models the hardware page table walk performed after a TLB miss. The
+/// page number is treated as a sequence of radix indices, one per page table level, and resolving
+/// each level costs one memory reference. With a paging-structure cache enabled, the walk may
+/// begin part way down the tree by reusing a cached upper-level entry, which is precisely the
+/// saving such caches exist to provide.
+///
+/// Note that the walk is only modeled for its cost. The page table itself remains a flat mapping
+/// and is consulted separately by the caller.
+pub struct PageWalker {
+    levels: usize,
+    bits_per_level: u32,
+    cache: Option<PagingStructureCache>,
+}
+
+impl PageWalker {
+    /// Create a new `PageWalker` for a page table with the given number of levels.
+    ///
+    /// # Arguments
+    ///
+    /// * `levels` - the number of page table levels (one implies a single-level table).
+    /// * `cache_size` - number of paging-structure cache entries; zero disables the cache.
+    pub fn build(levels: usize, cache_size: usize) -> Self {
+        let bits_page = MASK_PAGE.count_ones();
+        Self {
+            levels,
+            bits_per_level: bits_page.div_ceil(levels as u32),
+            cache: match cache_size {
+                0 => None,
+                size => Some(PagingStructureCache::build(size)),
+            },
+        }
+    }
+
+    /// Return the number of page table levels walked by this instance.
+    pub fn levels(&self) -> usize {
+        self.levels
+    }
+
+    /// Provided a page number and level, return the bits of the page number which index every
+    /// level from the root down to the level requested.
+    fn prefix(&self, page_number: usize, level: usize) -> usize {
+        let shift = self.bits_per_level as usize * (self.levels - 1 - level);
+        page_number.checked_shr(shift as u32).unwrap_or(0)
+    }
+
+    /// Walk the modeled page table for the provided page number and return the cost of doing so.
+    /// The deepest cached upper-level entry is used as the starting point when available.
+    /// Afterwards, every upper-level entry along the path is cached for future walks.
+    ///
+    /// # Arguments
+    ///
+    /// * `pid` - the process owning the page table.
+    /// * `page_number` - logical page number
+    pub fn walk(&mut self, pid: Pid, page_number: usize) -> WalkResult {
+        let upper_levels = self.levels - 1;
+        let prefixes: Vec<usize> = (0..upper_levels)
+            .map(|level| self.prefix(page_number, level))
+            .collect();
+
+        let cache = match self.cache.as_mut() {
+            Some(cache) => cache,
+            None => {
+                return WalkResult {
+                    cached_level: None,
+                    references: self.levels,
+                }
+            }
+        };
+
+        let cached_level = (0..upper_levels)
+            .rev()
+            .find(|&level| cache.find(pid, level, prefixes[level]));
+        prefixes
+            .iter()
+            .enumerate()
+            .for_each(|(level, prefix)| cache.cache_element(pid, level, *prefix));
+
+        WalkResult {
+            cached_level,
+            references: match cached_level {
+                Some(level) => self.levels - 1 - level,
+                None => self.levels,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[cfg(test)]
+    mod paging_structure_cache_tests {
+
+        use super::*;
+        const SIZE_TEST: usize = 2;
+
+        #[test]
+        fn build() {
+            let cache = PagingStructureCache::build(SIZE_TEST);
+            assert_eq!(cache.map.len(), 0);
+            assert_eq!(cache.table_size, SIZE_TEST);
+        }
+
+        #[test]
+        fn find_and_replace() {
+            let mut cache = PagingStructureCache::build(SIZE_TEST);
+            cache.cache_element(0, 0, 1);
+            cache.cache_element(0, 1, 1);
+            assert!(cache.find(0, 0, 1));
+            assert!(!cache.find(1, 0, 1));
+            cache.cache_element(0, 1, 2);
+            assert!(cache.find(0, 0, 1));
+            assert!(!cache.find(0, 1, 1));
+            assert!(cache.find(0, 1, 2));
+        }
+    }
+
+    #[cfg(test)]
+    mod page_walker_tests {
+
+        use super::*;
+
+        #[test]
+        fn walk_without_cache() {
+            let mut walker = PageWalker::build(4, 0);
+            (0..3).for_each(|_| {
+                assert_eq!(
+                    walker.walk(0, 0x12),
+                    WalkResult {
+                        cached_level: None,
+                        references: 4
+                    }
+                );
+            });
+        }
+
+        #[test]
+        fn walk_with_cache() {
+            let mut walker = PageWalker::build(4, 8);
+            assert_eq!(walker.walk(0, 0x12).cached_level, None);
+            assert_eq!(
+                walker.walk(0, 0x13),
+                WalkResult {
+                    cached_level: Some(2),
+                    references: 1
+                }
+            );
+            assert_eq!(
+                walker.walk(0, 0x1C),
+                WalkResult {
+                    cached_level: Some(1),
+                    references: 2
+                }
+            );
+            assert_eq!(
+                walker.walk(0, 0xF0),
+                WalkResult {
+                    cached_level: None,
+                    references: 4
+                }
+            );
+        }
+
+        #[test]
+        fn single_level() {
+            let mut walker = PageWalker::build(1, 8);
+            assert_eq!(walker.walk(0, 0x12).references, 1);
+            assert_eq!(walker.walk(0, 0x12).references, 1);
+        }
+    }
+}
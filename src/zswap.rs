@@ -0,0 +1,246 @@
+use crate::tracker::Tracker;
+use linked_hash_map::LinkedHashMap;
+
+/// The `CompressedPool` struct is a bounded in-memory cache of compressed swap pages placed in
+/// front of swap space, similar to zswap on Linux. A page written to a swap slot is compressed
+/// (see `compress`) and kept in the pool rather than written out, provided it compresses to less
+/// than its original size. Should the pool lack room, the oldest pages are evicted and handed back
+/// to the caller to be written to swap space. A pool with a capacity of zero holds nothing.
+///
+/// Pages are identified by their swap slot, so the slot of a page remains allocated while the
+/// page sits within the pool.
+pub struct CompressedPool {
+    capacity: usize,
+    used: usize,
+    entries: LinkedHashMap<usize, Vec<u8>>,
+    stores: usize,
+    hits: usize,
+    writebacks: usize,
+    original_bytes: usize,
+    compressed_bytes: usize,
+}
+
+impl CompressedPool {
+    /// Create a new, empty `CompressedPool` instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - the number of compressed bytes the pool may hold.
+    pub fn build(capacity: usize) -> Self {
+        Self {
+            capacity,
+            used: 0,
+            entries: LinkedHashMap::new(),
+            stores: 0,
+            hits: 0,
+            writebacks: 0,
+            original_bytes: 0,
+            compressed_bytes: 0,
+        }
+    }
+
+    /// Compress a page and store it under the provided swap slot, returning the pages evicted to
+    /// make room for it along with their slots, oldest first. `None` is returned, and nothing
+    /// stored, should the page not compress to less than its original size or not fit within the
+    /// pool at all.
+    ///
+    /// # Arguments
+    ///
+    /// * `slot` - the swap slot of the page.
+    /// * `buffer` - the contents of the page.
+    pub fn store(&mut self, slot: usize, buffer: &[u8]) -> Option<Vec<(usize, Vec<u8>)>> {
+        self.remove(slot);
+        let compressed = compress(buffer);
+        if compressed.len() >= buffer.len() || compressed.len() > self.capacity {
+            return None;
+        }
+        let mut evicted = Vec::new();
+        while self.used + compressed.len() > self.capacity {
+            let (victim, contents) = self.entries.pop_front()?;
+            self.used -= contents.len();
+            self.writebacks += 1;
+            evicted.push((victim, decompress(&contents)));
+        }
+        self.stores += 1;
+        self.original_bytes += buffer.len();
+        self.compressed_bytes += compressed.len();
+        self.used += compressed.len();
+        self.entries.insert(slot, compressed);
+        Some(evicted)
+    }
+
+    /// Should the pool hold the page of the provided swap slot, decompress it into the buffer and
+    /// return `true`.
+    ///
+    /// # Arguments
+    ///
+    /// * `slot` - the swap slot of the page.
+    /// * `buffer` - a mutable reference to a buffer for the page to be read into.
+    pub fn load(&mut self, slot: usize, buffer: &mut [u8]) -> bool {
+        let Some(contents) = self.entries.get(&slot) else {
+            return false;
+        };
+        buffer.copy_from_slice(&decompress(contents));
+        self.hits += 1;
+        true
+    }
+
+    /// Return whether the pool holds the page of the provided swap slot.
+    ///
+    /// # Arguments
+    ///
+    /// * `slot` - the swap slot of the page.
+    pub fn contains(&self, slot: usize) -> bool {
+        self.entries.contains_key(&slot)
+    }
+
+    /// Discard the page of the provided swap slot, should the pool hold it.
+    ///
+    /// # Arguments
+    ///
+    /// * `slot` - the swap slot of the page.
+    pub fn remove(&mut self, slot: usize) {
+        if let Some(contents) = self.entries.remove(&slot) {
+            self.used -= contents.len();
+        }
+    }
+
+    /// Copy the statistics of the pool into the provided tracker.
+    ///
+    /// # Arguments
+    ///
+    /// * `tracker` - the tracker receiving the statistics.
+    pub fn record(&self, tracker: &mut Tracker) {
+        tracker.zswap_stores = self.stores;
+        tracker.zswap_hits = self.hits;
+        tracker.zswap_writebacks = self.writebacks;
+        tracker.zswap_original_bytes = self.original_bytes;
+        tracker.zswap_compressed_bytes = self.compressed_bytes;
+    }
+}
+
+/// Compress a buffer with run-length encoding in the style of PackBits. The output is a series of
+/// packets, each beginning with a header byte. A header below 128 is followed by that many plus
+/// one literal bytes, while a header of 128 or above is followed by a single byte repeated the
+/// header less 125 times (runs of 3 to 130 bytes).
+///
+/// # Arguments
+///
+/// * `buffer` - the data to be compressed.
+pub fn compress(buffer: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut literals: Vec<u8> = Vec::new();
+    let mut index = 0;
+    while index < buffer.len() {
+        let run = buffer[index..]
+            .iter()
+            .take(130)
+            .take_while(|byte| **byte == buffer[index])
+            .count();
+        if run >= 3 {
+            flush_literals(&mut output, &mut literals);
+            output.extend([(run + 125) as u8, buffer[index]]);
+            index += run;
+        } else {
+            literals.push(buffer[index]);
+            if literals.len() == 128 {
+                flush_literals(&mut output, &mut literals);
+            }
+            index += 1;
+        }
+    }
+    flush_literals(&mut output, &mut literals);
+    output
+}
+
+/// Decompress a buffer produced by `compress`.
+///
+/// # Arguments
+///
+/// * `buffer` - the compressed data.
+pub fn decompress(buffer: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut index = 0;
+    while index < buffer.len() {
+        let header = buffer[index] as usize;
+        match header {
+            0..=127 => {
+                output.extend_from_slice(&buffer[index + 1..index + header + 2]);
+                index += header + 2;
+            }
+            _ => {
+                output.extend(std::iter::repeat_n(buffer[index + 1], header - 125));
+                index += 2;
+            }
+        }
+    }
+    output
+}
+
+/// Append any pending literal bytes to the compressed output as a single literal packet (see
+/// `compress`).
+///
+/// # Arguments
+///
+/// * `output` - the compressed output.
+/// * `literals` - the pending literal bytes, which are drained.
+fn flush_literals(output: &mut Vec<u8>, literals: &mut Vec<u8>) {
+    if literals.is_empty() {
+        return;
+    }
+    output.push((literals.len() - 1) as u8);
+    output.append(literals);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[cfg(test)]
+    mod compression_tests {
+
+        use super::*;
+
+        #[test]
+        fn round_trip() {
+            let mut buffer = vec![0_u8; 256];
+            buffer[10..14].copy_from_slice(&[1, 2, 3, 4]);
+            buffer[100..250].iter_mut().for_each(|x| *x = 9);
+            let compressed = compress(&buffer);
+            assert!(compressed.len() < 16);
+            assert_eq!(decompress(&compressed), buffer);
+
+            let noise: Vec<u8> = (0..=255).map(|x: u8| x.wrapping_mul(37)).collect();
+            let compressed = compress(&noise);
+            assert_eq!(compressed.len(), 258);
+            assert_eq!(decompress(&compressed), noise);
+        }
+    }
+
+    #[cfg(test)]
+    mod compressed_pool_tests {
+
+        use super::*;
+
+        #[test]
+        fn store_and_load() {
+            let mut pool = CompressedPool::build(4);
+            assert_eq!(pool.store(0, &[1; 8]), Some(vec![]));
+            assert_eq!(pool.store(1, &[1, 2, 3, 4, 5, 6, 7, 8]), None);
+            assert_eq!(pool.store(2, &[2; 8]), Some(vec![]));
+            assert_eq!(pool.store(3, &[3; 8]), Some(vec![(0, vec![1; 8])]));
+            assert!(!pool.contains(0));
+            assert_eq!((pool.stores, pool.writebacks), (3, 1));
+            assert_eq!((pool.original_bytes, pool.compressed_bytes), (24, 6));
+
+            let mut buffer = [0; 8];
+            assert!(pool.load(2, &mut buffer));
+            assert_eq!(buffer, [2; 8]);
+            assert!(!pool.load(0, &mut buffer));
+            pool.remove(2);
+            assert!(!pool.contains(2));
+            assert_eq!(pool.hits, 1);
+        }
+    }
+}